@@ -25,8 +25,9 @@ pub struct Changed {
 }
 
 impl Changed {
-    pub fn run(self, metadata: Metadata) -> Result {
+    pub fn run(mut self, metadata: Metadata) -> Result {
         let config: WorkspaceConfig = read_config(&metadata.workspace_metadata)?;
+        self.change.configure(&config);
         let mut since = self.since.clone();
 
         if self.since.is_none() {
@@ -0,0 +1,220 @@
+use crate::utils::{
+    changeset_path, get_group_packages, info, interactive, parse_level, read_config, Bump,
+    ChangeData, ChangeOpt, Changeset as ChangesetFile, Changesets, Error, GroupName, Result,
+    WorkspaceConfig, CHANGESET_DIR, INTERNAL_ERR,
+};
+
+use cargo_metadata::Metadata;
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Input, MultiSelect, Select};
+use oclif::{
+    console::style,
+    term::{TERM_ERR, TERM_OUT},
+};
+
+use std::{collections::BTreeMap as Map, fs};
+
+/// Manage changesets describing upcoming releases
+#[derive(Debug, Parser)]
+pub struct Changeset {
+    #[clap(subcommand)]
+    subcommand: ChangesetSubcommand,
+}
+
+#[derive(Debug, Parser)]
+enum ChangesetSubcommand {
+    Add(Add),
+    Status(Status),
+}
+
+/// Add a changeset naming the crates to release and how to bump them
+#[derive(Debug, Parser)]
+struct Add {
+    /// Crate and bump level to record, skipping the prompts (e.g. `foo=minor`)
+    #[clap(
+        long,
+        value_name = "crate=level",
+        multiple_occurrences = true,
+        parse(try_from_str = parse_crate_level)
+    )]
+    bump: Vec<(String, Bump)>,
+
+    /// Summary of the changes
+    #[clap(short, long, forbid_empty_values(true))]
+    message: Option<String>,
+
+    /// Also offer private crates
+    #[clap(short, long)]
+    all: bool,
+}
+
+/// List the pending changesets
+#[derive(Debug, Parser)]
+struct Status {}
+
+fn parse_crate_level(value: &str) -> std::result::Result<(String, Bump), String> {
+    let (name, level) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `crate=level`, found `{}`", value))?;
+
+    Ok((name.to_string(), parse_level(level)?))
+}
+
+impl Changeset {
+    pub fn run(self, metadata: Metadata) -> Result {
+        match self.subcommand {
+            ChangesetSubcommand::Add(x) => x.run(metadata),
+            ChangesetSubcommand::Status(x) => x.run(metadata),
+        }
+    }
+}
+
+impl Add {
+    fn run(self, metadata: Metadata) -> Result {
+        let config: WorkspaceConfig = read_config(&metadata.workspace_metadata)?;
+
+        let pkgs = get_group_packages(&metadata, &config, self.all)?
+            .into_iter()
+            .filter(|((group_name, _), _)| !matches!(group_name, GroupName::Excluded))
+            .collect::<Vec<_>>();
+
+        let mut bumps = Map::new();
+
+        if self.bump.is_empty() {
+            let mut change = ChangeOpt {
+                include_merged_tags: false,
+                force: None,
+                ignore_changes: None,
+            };
+            change.configure(&config);
+            let since = ChangeData::new(&metadata, &change)?.since;
+            let changed = if since.is_some() {
+                change
+                    .get_changed_pkgs(&metadata, &config, &since, &[], self.all)?
+                    .0
+                    .into_iter()
+                    .map(|(_, p)| p.name)
+                    .collect()
+            } else {
+                vec![]
+            };
+
+            let items = pkgs
+                .iter()
+                .map(|((group_name, _), p)| match group_name.pretty_fmt() {
+                    Some(group) => format!("{} {}", p.name, group),
+                    None => p.name.clone(),
+                })
+                .collect::<Vec<_>>();
+            let defaults = pkgs
+                .iter()
+                .map(|(_, p)| changed.contains(&p.name))
+                .collect::<Vec<_>>();
+
+            interactive("Which crates should be released?", "`--bump <crate=level>`")?;
+
+            let theme = ColorfulTheme::default();
+
+            let selected = MultiSelect::with_theme(&theme)
+                .with_prompt("Which crates should be released?")
+                .items(&items)
+                .defaults(&defaults)
+                .interact_on(&TERM_ERR)?;
+
+            for i in selected {
+                let (_, p) = pkgs.get(i).expect(INTERNAL_ERR);
+
+                let level = Select::with_theme(&theme)
+                    .with_prompt(format!("Select a bump level for {}", p.name))
+                    .items(&["patch", "minor", "major"])
+                    .default(0)
+                    .interact_on(&TERM_ERR)?;
+
+                bumps.insert(
+                    p.name.clone(),
+                    match level {
+                        2 => Bump::Major,
+                        1 => Bump::Minor,
+                        _ => Bump::Patch,
+                    },
+                );
+            }
+        } else {
+            for (name, bump) in self.bump {
+                if !pkgs.iter().any(|(_, p)| p.name == name) {
+                    return Err(Error::PackageNotFound { id: name });
+                }
+                bumps.insert(name, bump);
+            }
+        }
+
+        if bumps.is_empty() {
+            TERM_OUT.write_line("No crates selected, skipping changeset")?;
+            return Ok(());
+        }
+
+        let summary = match self.message {
+            Some(message) => message,
+            None => {
+                interactive("Summary of the changes", "`--message`")?;
+
+                Input::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Summary of the changes")
+                    .interact_on(&TERM_ERR)?
+            }
+        };
+
+        let changeset = ChangesetFile {
+            path: changeset_path(&metadata.workspace_root, &summary),
+            bumps,
+            summary,
+        };
+
+        fs::create_dir_all(metadata.workspace_root.join(CHANGESET_DIR))?;
+        fs::write(&changeset.path, changeset.render())?;
+
+        info!(
+            "created",
+            changeset
+                .path
+                .strip_prefix(&metadata.workspace_root)
+                .unwrap_or(&changeset.path)
+        );
+
+        Ok(())
+    }
+}
+
+impl Status {
+    fn run(self, metadata: Metadata) -> Result {
+        let changesets = Changesets::read(&metadata.workspace_root)?;
+
+        if changesets.is_empty() {
+            TERM_OUT.write_line("No pending changesets")?;
+            return Ok(());
+        }
+
+        for changeset in &changesets.0 {
+            TERM_OUT.write_line(&format!(
+                "{}",
+                style(
+                    changeset
+                        .path
+                        .strip_prefix(&metadata.workspace_root)
+                        .unwrap_or(&changeset.path)
+                )
+                .bold()
+            ))?;
+
+            for (name, bump) in &changeset.bumps {
+                TERM_OUT.write_line(&format!(" - {}: {}", style(name).yellow(), bump))?;
+            }
+
+            if let Some(line) = changeset.summary.lines().next() {
+                TERM_OUT.write_line(&format!("   {}", style(line).black().bright()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use crate::utils::{
-    cargo, change_versions, info, Error, ManifestDiscriminant, Result, INTERNAL_ERR,
+    cargo, change_versions, info, interactive, Error, ManifestDiscriminant, Result, INTERNAL_ERR,
 };
 
 use cargo_metadata::Metadata;
@@ -53,9 +53,13 @@ impl Create {
 
         let name = match &self.name {
             Some(n) => n.clone(),
-            None => Input::with_theme(&theme)
-                .with_prompt("Name of the crate")
-                .interact_on(&TERM_ERR)?,
+            None => {
+                interactive("Name of the crate", "`--name`")?;
+
+                Input::with_theme(&theme)
+                    .with_prompt("Name of the crate")
+                    .interact_on(&TERM_ERR)?
+            }
         };
 
         let types = vec!["library", "binary"];
@@ -65,6 +69,8 @@ impl Create {
         } else if self.bin {
             1
         } else {
+            interactive("Type of the crate", "`--lib` or `--bin`")?;
+
             Select::with_theme(&theme)
                 .items(&types)
                 .default(1)
@@ -83,11 +89,15 @@ impl Create {
                 &Edition::Eighteen => 1,
                 &Edition::TwentyOne => 2,
             },
-            None => Select::with_theme(&theme)
-                .items(&editions)
-                .default(2)
-                .with_prompt("Rust edition")
-                .interact_on(&TERM_ERR)?,
+            None => {
+                interactive("Rust edition", "`--edition`")?;
+
+                Select::with_theme(&theme)
+                    .items(&editions)
+                    .default(2)
+                    .with_prompt("Rust edition")
+                    .interact_on(&TERM_ERR)?
+            }
         };
 
         let mut args = vec![
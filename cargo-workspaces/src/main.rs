@@ -1,8 +1,10 @@
 mod changed;
+mod changeset;
 mod create;
 mod exec;
 mod init;
 mod list;
+mod pre;
 mod publish;
 mod rename;
 mod version;
@@ -13,12 +15,19 @@ use cargo_metadata::{CargoOpt, MetadataCommand};
 use clap::Parser;
 use oclif::finish;
 
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
 #[derive(Debug, Parser)]
 enum Subcommand {
     // TODO: add
     List(list::List),
     Changed(changed::Changed),
     Version(version::Version),
+    Changeset(changeset::Changeset),
+    Pre(pre::Pre),
     Publish(publish::Publish),
     Exec(exec::Exec),
     Create(create::Create),
@@ -41,6 +50,10 @@ struct Opt {
     #[clap(short)]
     verbose: bool,
 
+    /// Fail instead of prompting, implied when stdin isn't a terminal or `CI` is set
+    #[clap(long)]
+    no_interactive: bool,
+
     #[clap(subcommand)]
     subcommand: Subcommand,
 }
@@ -61,6 +74,10 @@ fn main() {
         utils::set_debug();
     }
 
+    if opt.no_interactive || is_ci() || !io::stdin().is_terminal() {
+        utils::set_no_interactive();
+    }
+
     let result = if let Subcommand::Init(ref init) = opt.subcommand {
         init.run()
     } else {
@@ -80,6 +97,8 @@ fn main() {
             Subcommand::List(x) => x.run(metadata),
             Subcommand::Changed(x) => x.run(metadata),
             Subcommand::Version(x) => x.run(metadata),
+            Subcommand::Changeset(x) => x.run(metadata),
+            Subcommand::Pre(x) => x.run(metadata),
             Subcommand::Publish(x) => x.run(metadata),
             Subcommand::Exec(x) => x.run(metadata),
             Subcommand::Create(x) => x.run(metadata),
@@ -91,6 +110,10 @@ fn main() {
     finish(result)
 }
 
+fn is_ci() -> bool {
+    env::var("CI").is_ok_and(|x| x == "true" || x == "1")
+}
+
 fn set_handlers() {
     // https://github.com/console-rs/dialoguer/issues/77
     ctrlc::set_handler(move || {
@@ -0,0 +1,75 @@
+use crate::utils::{info, validate_pre_tag, Error, PreMode, PreState, Result};
+
+use cargo_metadata::Metadata;
+use clap::Parser;
+
+/// Enter or exit prerelease mode
+#[derive(Debug, Parser)]
+pub struct Pre {
+    #[clap(subcommand)]
+    subcommand: PreSubcommand,
+}
+
+#[derive(Debug, Parser)]
+enum PreSubcommand {
+    Enter(Enter),
+    Exit(Exit),
+}
+
+/// Release every following version as a prerelease with the given tag
+#[derive(Debug, Parser)]
+struct Enter {
+    /// Prerelease identifier, like `alpha`, `beta` or `rc`
+    #[clap(validator = validate_pre_tag)]
+    tag: String,
+}
+
+/// Graduate the prereleases to stable versions on the next release
+#[derive(Debug, Parser)]
+struct Exit {}
+
+impl Pre {
+    pub fn run(self, metadata: Metadata) -> Result {
+        let root = &metadata.workspace_root;
+        let state = PreState::read(root)?;
+
+        match self.subcommand {
+            PreSubcommand::Enter(enter) => {
+                if let Some(PreState {
+                    mode: PreMode::Pre,
+                    tag,
+                }) = state
+                {
+                    return Err(Error::AlreadyInPreMode { tag });
+                }
+
+                PreState {
+                    mode: PreMode::Pre,
+                    tag: enter.tag.clone(),
+                }
+                .write(root)?;
+
+                info!("entered", format!("prerelease mode with tag {}", enter.tag));
+            }
+            PreSubcommand::Exit(_) => {
+                let state = match state {
+                    Some(state) if state.mode == PreMode::Pre => state,
+                    _ => return Err(Error::NotInPreMode),
+                };
+
+                PreState {
+                    mode: PreMode::Exit,
+                    ..state
+                }
+                .write(root)?;
+
+                info!(
+                    "exiting",
+                    "prerelease mode, the next version will graduate the prereleases"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
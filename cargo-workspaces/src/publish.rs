@@ -1,11 +1,13 @@
 use crate::utils::{
-    cargo, cargo_config_get, check_index, dag, info, is_published, read_config, Error, Result,
-    VersionOpt, INTERNAL_ERR,
+    cargo, cargo_config_get, check_index, dag, info, is_published, read_config, validate_pre_tag,
+    Error, Result, VersionOpt, WorkspaceConfig, INTERNAL_ERR,
 };
-use cargo_metadata::Metadata;
+use cargo_metadata::{Metadata, Package};
 use clap::Parser;
 use crates_index::Index;
 use indexmap::IndexSet as Set;
+use oclif::{term::TERM_OUT, CliError};
+use semver::Version;
 
 /// Publish crates in the project
 #[derive(Debug, Parser)]
@@ -27,10 +29,6 @@ pub struct Publish {
     #[clap(long)]
     no_verify: bool,
 
-    /// Allow dirty working directories to be published
-    #[clap(long)]
-    allow_dirty: bool,
-
     /// The token to use for publishing
     #[clap(long, forbid_empty_values(true))]
     token: Option<String>,
@@ -38,17 +36,32 @@ pub struct Publish {
     /// The Cargo registry to use for publishing
     #[clap(long, forbid_empty_values(true))]
     registry: Option<String>,
+
+    /// Publish the changed crates and their dependents as prereleases of the current commit,
+    /// restoring the manifests afterwards without committing, tagging or pushing
+    #[clap(
+        long,
+        value_name = "preid",
+        validator = validate_pre_tag,
+        conflicts_with = "from-git"
+    )]
+    canary: Option<Option<String>>,
 }
 
+const DEFAULT_CANARY_ID: &str = "canary";
+
 impl Publish {
-    pub fn run(self, metadata: Metadata) -> Result {
+    pub fn run(mut self, metadata: Metadata) -> Result {
         let config = read_config(&metadata.workspace_metadata)?;
+        self.version.configure(&config)?;
+
+        if let Some(preid) = &self.canary {
+            let preid = preid.as_deref().unwrap_or(DEFAULT_CANARY_ID);
+            return self.publish_canary(&metadata, &config, preid);
+        }
 
         let mut versions = None;
-        let branch = self
-            .version
-            .git
-            .validate(&metadata.workspace_root, &config)?;
+        let branch = self.version.git.validate(&metadata, &config)?;
 
         let pkgs = if !self.from_git {
             let mut new_versions = vec![];
@@ -76,7 +89,92 @@ impl Publish {
                 .collect()
         };
 
-        let (names, visited) = dag(&pkgs);
+        let mut tags = vec![];
+        for (pkg, version) in self.publish(&metadata, &pkgs)? {
+            if let Some(tag) = self.version.git.individual_tag(
+                &metadata.workspace_root,
+                &pkg.name,
+                pkg.publish.as_ref().map_or(false, Vec::is_empty),
+                &version,
+            )? {
+                tags.push(tag)
+            }
+        }
+
+        if let Some((Some(new_version), new_versions)) = versions {
+            if let Some(tag) = self.version.git.global_tag(
+                &metadata.workspace_root,
+                &new_version,
+                &new_versions,
+            )? {
+                tags.push(tag)
+            }
+
+            self.version
+                .git
+                .push(&metadata.workspace_root, &branch, &tags)?;
+        }
+
+        info!("success", "ok");
+        Ok(())
+    }
+
+    /// Temporarily version the changed crates as canaries and publish them, putting the
+    /// manifests back whether publishing succeeded or not
+    fn publish_canary(&self, metadata: &Metadata, config: &WorkspaceConfig, preid: &str) -> Result {
+        let new_versions = self.version.canary_versions(metadata, config, preid)?;
+
+        if new_versions.is_empty() {
+            TERM_OUT.write_line("No changes detected, skipping canary")?;
+            return Ok(());
+        }
+
+        // Kept until the manifests are put back, so `version --restore` can undo an interrupted canary
+        let transaction = self
+            .version
+            .rewrite_restorable(metadata, config, &new_versions)?;
+
+        let pkgs = new_versions
+            .values()
+            .map(|(pkg, ver)| {
+                (
+                    metadata
+                        .packages
+                        .iter()
+                        .find(|y| pkg.id == y.id)
+                        .expect(INTERNAL_ERR),
+                    ver.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let published = self.publish(metadata, &pkgs);
+
+        info!("restoring", "the manifests versioned for the canary");
+
+        if let Err(rollback_err) = transaction.rollback() {
+            if let Err(err) = published {
+                err.print()?;
+            }
+            return Err(rollback_err);
+        }
+
+        self.version.forget_restorable(metadata)?;
+
+        published?;
+
+        info!("success", "ok");
+        Ok(())
+    }
+
+    /// Publish the crates in dependency order, skipping private crates and versions that
+    /// are already published, returning the publishable crates
+    fn publish<'a>(
+        &self,
+        metadata: &Metadata,
+        pkgs: &'a [(&'a Package, Version)],
+    ) -> Result<Vec<(&'a Package, String)>> {
+        let (names, visited) = dag(pkgs);
 
         // Filter out private packages
         let visited = visited
@@ -91,7 +189,7 @@ impl Publish {
             })
             .collect::<Set<_>>();
 
-        let mut tags = vec![];
+        let mut published = vec![];
         for p in &visited {
             let (pkg, version) = names.get(p).expect(INTERNAL_ERR);
             let version = version.to_string();
@@ -123,7 +221,8 @@ impl Publish {
                     args.push("--no-verify");
                 }
 
-                if self.allow_dirty {
+                // The canary versions are only in the working directory
+                if self.version.git.allow_dirty || self.canary.is_some() {
                     args.push("--allow-dirty");
                 }
 
@@ -151,32 +250,9 @@ impl Publish {
                 info!("published", name_ver);
             }
 
-            if let Some(tag) = self.version.git.individual_tag(
-                &metadata.workspace_root,
-                &pkg.name,
-                pkg.publish.as_ref().map_or(false, Vec::is_empty),
-                &version,
-                &config,
-            )? {
-                tags.push(tag)
-            }
+            published.push((*pkg, version));
         }
 
-        if let Some((Some(new_version), new_versions)) = versions {
-            if let Some(tag) = self.version.git.global_tag(
-                &metadata.workspace_root,
-                &new_version,
-                &new_versions,
-            )? {
-                tags.push(tag)
-            }
-
-            self.version
-                .git
-                .push(&metadata.workspace_root, &branch, &tags)?;
-        }
-
-        info!("success", "ok");
-        Ok(())
+        Ok(published)
     }
 }
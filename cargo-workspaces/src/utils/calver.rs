@@ -0,0 +1,158 @@
+use crate::utils::{Date, Error, Result};
+
+use semver::Version;
+
+use std::convert::TryInto;
+
+pub const DEFAULT_CALVER_FORMAT: &str = "YYYY.MM.MICRO";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    /// Full year, like `2026`
+    Year,
+    /// Year since 2000, like `26`
+    ShortYear,
+    Month,
+    Day,
+    /// Counts up the releases made within the same period, starting at `0`
+    Micro,
+}
+
+/// A calendar version format like `YYYY.MM.MICRO`, with a part for each of the major,
+/// minor and patch numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalverFormat([Part; 3]);
+
+impl CalverFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        let err = |msg: &str| Error::BadCalverFormat {
+            format: format.to_string(),
+            msg: msg.to_string(),
+        };
+
+        let parts = format
+            .split('.')
+            .map(|part| match part {
+                "YYYY" => Ok(Part::Year),
+                "YY" => Ok(Part::ShortYear),
+                "MM" => Ok(Part::Month),
+                "DD" => Ok(Part::Day),
+                "MICRO" => Ok(Part::Micro),
+                _ => Err(err(&format!(
+                    "unknown part `{}`, expected one of `YYYY`, `YY`, `MM`, `DD` or `MICRO`",
+                    part
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let parts: [Part; 3] = parts
+            .try_into()
+            .map_err(|_| err("expected three parts, like `YYYY.MM.MICRO`"))?;
+
+        if parts[..2].contains(&Part::Micro) {
+            return Err(err("`MICRO` can only be the last part"));
+        }
+
+        Ok(Self(parts))
+    }
+
+    /// The version of a release on the date, counting up `MICRO` from the current version
+    /// when it was released in the same period
+    pub fn next(&self, cur_version: &Version, date: &Date) -> Result<Version> {
+        let value = |part| match part {
+            Part::Year => date.year,
+            Part::ShortYear => date.year.saturating_sub(2000),
+            Part::Month => date.month,
+            Part::Day => date.day,
+            Part::Micro => 0,
+        };
+
+        let cur = [cur_version.major, cur_version.minor, cur_version.patch];
+        let same_period = self
+            .0
+            .iter()
+            .zip(cur)
+            .all(|(part, cur)| *part == Part::Micro || value(*part) == cur);
+
+        let numbers = self
+            .0
+            .iter()
+            .zip(cur)
+            .map(|(part, cur)| match part {
+                // A prerelease of the period is released as is
+                Part::Micro if same_period && cur_version.is_prerelease() => cur,
+                Part::Micro if same_period => cur + 1,
+                part => value(*part),
+            })
+            .collect::<Vec<_>>();
+        let next = Version::new(numbers[0], numbers[1], numbers[2]);
+
+        if next <= *cur_version {
+            return Err(Error::CalverNotAfter {
+                version: cur_version.to_string(),
+                next: next.to_string(),
+            });
+        }
+
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn next(format: &str, cur: &str, date: (u64, u64, u64)) -> Result<String> {
+        let date = Date {
+            year: date.0,
+            month: date.1,
+            day: date.2,
+        };
+
+        CalverFormat::parse(format)?
+            .next(&Version::parse(cur).unwrap(), &date)
+            .map(|x| x.to_string())
+    }
+
+    #[test]
+    fn test_new_period() {
+        assert_eq!(
+            next("YYYY.MM.MICRO", "2026.9.3", (2026, 10, 18)).unwrap(),
+            "2026.10.0"
+        );
+        assert_eq!(
+            next("YYYY.MM.MICRO", "0.1.0", (2026, 10, 18)).unwrap(),
+            "2026.10.0"
+        );
+        assert_eq!(
+            next("YY.MM.DD", "26.10.17", (2026, 10, 18)).unwrap(),
+            "26.10.18"
+        );
+    }
+
+    #[test]
+    fn test_same_period() {
+        assert_eq!(
+            next("YYYY.MM.MICRO", "2026.10.0", (2026, 10, 18)).unwrap(),
+            "2026.10.1"
+        );
+        assert_eq!(
+            next("YYYY.MM.MICRO", "2026.10.2-rc.1", (2026, 10, 18)).unwrap(),
+            "2026.10.2"
+        );
+    }
+
+    #[test]
+    fn test_not_after() {
+        assert!(next("YYYY.MM.DD", "2026.10.18", (2026, 10, 18)).is_err());
+        assert!(next("YYYY.MM.MICRO", "2027.1.0", (2026, 10, 18)).is_err());
+    }
+
+    #[test]
+    fn test_bad_format() {
+        assert!(CalverFormat::parse("YYYY.MM").is_err());
+        assert!(CalverFormat::parse("YYYY.MICRO.DD").is_err());
+        assert!(CalverFormat::parse("YYYY.0M.MICRO").is_err());
+        assert!(CalverFormat::parse("YYYY.MM.DD.MICRO").is_err());
+    }
+}
@@ -19,6 +19,7 @@ use std::{
 
 const CRLF: &str = "\r\n";
 const LF: &str = "\n";
+const LOCK_PACKAGE: &str = "[[package]]";
 
 lazy_static! {
     static ref NAME: Regex =
@@ -77,6 +78,8 @@ lazy_static! {
             .expect(INTERNAL_ERR);
     static ref WORKSPACE_KEY: Regex =
         Regex::new(r#"['"]?workspace['"]?\s*=\s*true"#).expect(INTERNAL_ERR);
+    static ref LOCK_DEP: Regex =
+        Regex::new(r#"^(\s*")([0-9A-Za-z-_]+) ([^\s"]+)(",?)$"#).expect(INTERNAL_ERR);
 }
 
 pub fn cargo<'a>(
@@ -505,6 +508,76 @@ pub fn change_versions(
     )
 }
 
+/// Change the versions of workspace members in a `Cargo.lock`
+///
+/// Members are the entries without a `source`, dependencies on them are only
+/// rewritten where the lock disambiguates them by version.
+pub fn change_lock_versions(lock: &str, versions: &Map<String, (Version, Version)>) -> String {
+    let mut new_lock = String::new();
+    let mut entry = vec![];
+
+    // Lines keep their own endings, so CRLF lockfiles and the final newline are left as is
+    for line in lock.split_inclusive('\n') {
+        let content = line.trim_end_matches(&['\r', '\n'][..]);
+
+        if content == LOCK_PACKAGE {
+            push_lock_entry(&mut new_lock, std::mem::take(&mut entry), versions);
+        }
+
+        entry.push((content.to_string(), &line[content.len()..]));
+    }
+
+    push_lock_entry(&mut new_lock, entry, versions);
+
+    new_lock
+}
+
+fn push_lock_entry(
+    new_lock: &mut String,
+    entry: Vec<(String, &str)>,
+    versions: &Map<String, (Version, Version)>,
+) {
+    let (lines, endings): (Vec<_>, Vec<_>) = entry.into_iter().unzip();
+
+    for (line, ending) in edit_lock_entry(lines, versions).into_iter().zip(endings) {
+        new_lock.push_str(&line);
+        new_lock.push_str(ending);
+    }
+}
+
+fn edit_lock_entry(
+    mut lines: Vec<String>,
+    versions: &Map<String, (Version, Version)>,
+) -> Vec<String> {
+    let is_member = lines.first().map(String::as_str) == Some(LOCK_PACKAGE)
+        && !lines.iter().any(|line| line.starts_with("source = "));
+
+    let name = lines
+        .iter()
+        .find_map(|line| NAME.captures(line).map(|caps| caps[2].to_string()));
+
+    for line in lines.iter_mut() {
+        if let Some(caps) = LOCK_DEP.captures(line) {
+            if let Some((old_version, new_version)) = versions.get(&caps[2]) {
+                if caps[3] == old_version.to_string() {
+                    *line = format!("{}{} {}{}", &caps[1], &caps[2], new_version, &caps[4]);
+                }
+            }
+        } else if is_member {
+            if let (Some(caps), Some((old_version, new_version))) = (
+                VERSION.captures(line),
+                name.as_ref().and_then(|name| versions.get(name)),
+            ) {
+                if caps[2] == old_version.to_string() {
+                    *line = format!("{}{}{}", &caps[1], new_version, &caps[3]);
+                }
+            }
+        }
+    }
+
+    lines
+}
+
 pub trait VersionSpec {
     fn is_unversioned(other: &Self) -> bool;
 }
@@ -1650,4 +1723,77 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_lock_versions() {
+        let m = indoc! {r#"
+            version = 3
+
+            [[package]]
+            name = "dep1"
+            version = "0.1.0"
+
+            [[package]]
+            name = "dep1"
+            version = "0.1.0"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "top"
+            version = "0.1.0"
+            dependencies = [
+             "dep1 0.1.0",
+             "dep1 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+             "serde",
+            ]
+        "#};
+
+        let mut v = Map::new();
+        v.insert(
+            "dep1".to_string(),
+            ("0.1.0".parse().unwrap(), "0.2.0".parse().unwrap()),
+        );
+
+        assert_eq!(
+            change_lock_versions(m, &v),
+            indoc! {r#"
+                version = 3
+
+                [[package]]
+                name = "dep1"
+                version = "0.2.0"
+
+                [[package]]
+                name = "dep1"
+                version = "0.1.0"
+                source = "registry+https://github.com/rust-lang/crates.io-index"
+
+                [[package]]
+                name = "top"
+                version = "0.1.0"
+                dependencies = [
+                 "dep1 0.2.0",
+                 "dep1 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                 "serde",
+                ]
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_lock_versions_crlf() {
+        let m = "version = 3\r\n\r\n[[package]]\r\nname = \"dep1\"\r\nversion = \"0.1.0\"\r\n";
+
+        let mut v = Map::new();
+        v.insert(
+            "dep1".to_string(),
+            ("0.1.0".parse().unwrap(), "0.2.0".parse().unwrap()),
+        );
+
+        assert_eq!(change_lock_versions(m, &v), m.replace("0.1.0", "0.2.0"));
+        assert_eq!(
+            change_lock_versions(m.trim_end(), &v),
+            m.trim_end().replace("0.1.0", "0.2.0")
+        );
+    }
 }
@@ -74,6 +74,18 @@ impl ChangeData {
 }
 
 impl ChangeOpt {
+    /// Fill in the options missing from the command line with the workspace config
+    pub fn configure(&mut self, config: &WorkspaceConfig) {
+        self.include_merged_tags |= config.include_merged_tags.unwrap_or_default();
+
+        if self.force.is_none() {
+            self.force = config.force.clone();
+        }
+        if self.ignore_changes.is_none() {
+            self.ignore_changes = config.ignore_changes.clone();
+        }
+    }
+
     pub fn get_changed_pkgs<'a>(
         &self,
         metadata: &Metadata,
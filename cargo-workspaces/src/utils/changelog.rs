@@ -0,0 +1,703 @@
+use crate::utils::{
+    git, today, Bump, Error, GitOpt, Pkg, Release, Result, Transaction, WorkspaceConfig,
+    INTERNAL_ERR, TRAILER,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::Metadata;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use std::{fs, path::Path};
+
+const DEFAULT_CHANGELOG: &str = "CHANGELOG.md";
+const HEADER: &str = "# Changelog";
+
+lazy_static! {
+    static ref CONVENTIONAL: Regex =
+        Regex::new(r"^([A-Za-z]+)(?:\(([^)]*)\))?(!)?:\s*(.+)$").expect(INTERNAL_ERR);
+    static ref BREAKING_FOOTER: Regex =
+        Regex::new(r"(?m)^BREAKING[ -]CHANGE:").expect(INTERNAL_ERR);
+    static ref SCP_REMOTE: Regex =
+        Regex::new(r"^(?:[^@/]+@)?([^:/]+):(.+?)(?:\.git)?/?$").expect(INTERNAL_ERR);
+    static ref URL_REMOTE: Regex =
+        Regex::new(r"^(?:https?|ssh|git)://(?:[^@/]+@)?([^/:]+)(?::\d+)?/(.+?)(?:\.git)?/?$")
+            .expect(INTERNAL_ERR);
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, Ord, Eq, PartialOrd, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangelogFormat {
+    /// Group commits by their conventional commit type
+    #[default]
+    Conventional,
+    /// List commits as they were made
+    Plain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub sha: String,
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    pub subject: String,
+    pub breaking: bool,
+}
+
+impl Commit {
+    pub fn parse(sha: &str, subject: &str, body: &str) -> Self {
+        let breaking = BREAKING_FOOTER.is_match(body);
+
+        if let Some(caps) = CONVENTIONAL.captures(subject) {
+            return Self {
+                sha: sha.to_string(),
+                kind: Some(caps[1].to_lowercase()),
+                scope: caps.get(2).map(|x| x.as_str().to_string()),
+                subject: caps[4].to_string(),
+                breaking: breaking || caps.get(3).is_some(),
+            };
+        }
+
+        Self {
+            sha: sha.to_string(),
+            kind: None,
+            scope: None,
+            subject: subject.to_string(),
+            breaking,
+        }
+    }
+
+    /// The subject along with its conventional type and scope
+    pub fn headline(&self) -> String {
+        match (&self.kind, &self.scope) {
+            (Some(kind), Some(scope)) => format!("{}({}): {}", kind, scope, self.subject),
+            (Some(kind), None) => format!("{}: {}", kind, self.subject),
+            _ => self.subject.clone(),
+        }
+    }
+
+    pub fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+
+    fn section(&self) -> Option<&'static str> {
+        match self.kind.as_deref() {
+            Some("feat") => Some("Features"),
+            Some("fix") => Some("Bug Fixes"),
+            Some("perf") => Some("Performance Improvements"),
+            Some("revert") => Some("Reverts"),
+            Some("docs") => Some("Documentation"),
+            Some("refactor") => Some("Refactors"),
+            Some("build" | "chore" | "ci" | "style" | "test") => None,
+            _ => Some("Other Changes"),
+        }
+    }
+}
+
+const SECTIONS: &[&str] = &[
+    "Features",
+    "Bug Fixes",
+    "Performance Improvements",
+    "Reverts",
+    "Documentation",
+    "Refactors",
+    "Other Changes",
+];
+
+/// Directories of the workspace members relative to the root, leaving out the root crate
+pub fn member_paths(metadata: &Metadata) -> Vec<String> {
+    let root = &metadata.workspace_root;
+
+    metadata
+        .packages
+        .iter()
+        .filter_map(|p| {
+            p.manifest_path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(root).ok())
+                .map(slash_path)
+        })
+        .filter(|dir| !dir.is_empty())
+        .collect()
+}
+
+/// The relative path with `/` separators, as git expects in pathspecs on every platform
+pub fn slash_path<P: AsRef<Path>>(path: P) -> String {
+    path.as_ref()
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Members nested in any of the paths, which aren't one of the paths themselves
+pub fn nested_paths(member_paths: &[String], paths: &[&str]) -> Vec<String> {
+    member_paths
+        .iter()
+        .filter(|x| {
+            let member = Utf8Path::new(x.as_str());
+
+            paths.iter().all(|path| member != Utf8Path::new(path))
+                && paths
+                    .iter()
+                    .any(|path| *path == "." || member.starts_with(path))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Commits of the crate since its previous individual tag, or since `since` without one
+fn pkg_commits(
+    root: &Utf8PathBuf,
+    git_opt: &GitOpt,
+    member_paths: &[String],
+    name: &str,
+    pkg: &Pkg,
+    since: Option<&str>,
+) -> Result<Vec<Commit>> {
+    let path = slash_path(&pkg.path);
+    let exclude = nested_paths(member_paths, &[&path]);
+
+    let previous_tag = git_opt.individual_tag_name(name, &pkg.version.to_string());
+    let (found, _, _) = git(
+        root,
+        &[
+            "rev-parse",
+            "-q",
+            "--verify",
+            &format!("refs/tags/{}", previous_tag),
+        ],
+    )?;
+    let since = if found.success() {
+        Some(previous_tag.as_str())
+    } else {
+        since
+    };
+
+    commits(root, since, &[&path], &exclude)
+}
+
+fn pathspecs<'a>(paths: &[&'a str], exclude: &'a [String]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|x| x.to_string())
+        .chain(exclude.iter().map(|x| format!(":(exclude){}", x)))
+        .collect()
+}
+
+/// Commits since `since` that touched the paths, leaving out the ones from nested crates
+pub fn commits(
+    root: &Utf8PathBuf,
+    since: Option<&str>,
+    paths: &[&str],
+    exclude: &[String],
+) -> Result<Vec<Commit>> {
+    let range = since.map_or("HEAD".to_string(), |since| format!("{}..HEAD", since));
+    let pathspecs = pathspecs(paths, exclude);
+
+    let mut args = vec!["log", "--format=%H%x1f%s%x1f%b%x1e", &range, "--"];
+    args.extend(pathspecs.iter().map(|x| x.as_str()));
+
+    let (status, out, err) = git(root, &args)?;
+
+    if !status.success() {
+        return Err(Error::GitFailed {
+            args: args.join(" "),
+            err,
+        });
+    }
+
+    Ok(out
+        .split('\x1e')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(3, '\x1f');
+            let sha = parts.next()?;
+            let subject = parts.next().unwrap_or_default();
+            let body = parts.next().unwrap_or_default();
+
+            // Skip earlier release commits
+            if body.lines().any(|x| x == TRAILER) {
+                return None;
+            }
+
+            Some(Commit::parse(sha, subject, body))
+        })
+        .collect())
+}
+
+/// Summary of the files changed in the paths since `since`, leaving out nested crates
+pub fn diffstat(
+    root: &Utf8PathBuf,
+    since: &str,
+    paths: &[&str],
+    exclude: &[String],
+) -> Result<Vec<String>> {
+    let pathspecs = pathspecs(paths, exclude);
+
+    let mut args = vec!["diff", "--stat=100", since, "HEAD", "--"];
+    args.extend(pathspecs.iter().map(|x| x.as_str()));
+
+    let (status, out, err) = git(root, &args)?;
+
+    if !status.success() {
+        return Err(Error::GitFailed {
+            args: args.join(" "),
+            err,
+        });
+    }
+
+    Ok(out.lines().map(|x| x.trim_start().to_string()).collect())
+}
+
+struct Links(Option<String>);
+
+impl Links {
+    fn new(root: &Utf8PathBuf, git_opt: &GitOpt, config: &WorkspaceConfig) -> Result<Self> {
+        if let Some(url) = config.changelog.as_ref().and_then(|x| x.commit_url.clone()) {
+            return Ok(Self(Some(url)));
+        }
+
+        let (_, remote, _) = git(root, &["remote", "get-url", git_opt.git_remote()])?;
+
+        Ok(Self(
+            remote_web_url(&remote).map(|base| format!("{}/commit/%H", base)),
+        ))
+    }
+
+    fn render(&self, commit: &Commit) -> String {
+        match &self.0 {
+            Some(url) => format!(
+                "[{}]({})",
+                commit.short_sha(),
+                url.replace("%H", &commit.sha)
+                    .replace("%h", commit.short_sha())
+            ),
+            None => format!("`{}`", commit.short_sha()),
+        }
+    }
+}
+
+fn remote_web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+
+    let caps = if remote.contains("://") {
+        URL_REMOTE.captures(remote)?
+    } else {
+        SCP_REMOTE.captures(remote)?
+    };
+
+    Some(format!("https://{}/{}", &caps[1], &caps[2]))
+}
+
+fn render_entry(commit: &Commit, links: &Links) -> String {
+    match &commit.scope {
+        Some(scope) => format!(
+            "- **{}:** {} ({})",
+            scope,
+            commit.subject,
+            links.render(commit)
+        ),
+        None => format!("- {} ({})", commit.subject, links.render(commit)),
+    }
+}
+
+fn render_note(summary: &str) -> String {
+    format!("- {}", summary.lines().collect::<Vec<_>>().join("\n  "))
+}
+
+fn render_entries(
+    notes: &[(&Bump, &str)],
+    commits: &[Commit],
+    format: ChangelogFormat,
+    links: &Links,
+    level: usize,
+) -> Vec<String> {
+    let heading = "#".repeat(level);
+    let mut lines = vec![];
+
+    match format {
+        ChangelogFormat::Plain => {
+            lines.extend(notes.iter().map(|(_, summary)| render_note(summary)));
+
+            for commit in commits {
+                let entry = render_entry(commit, links);
+                if commit.breaking {
+                    lines.push(format!("{} [BREAKING]", entry));
+                } else {
+                    lines.push(entry);
+                }
+            }
+        }
+        ChangelogFormat::Conventional => {
+            for (bump, title) in &[
+                (Bump::Major, "Major Changes"),
+                (Bump::Minor, "Minor Changes"),
+                (Bump::Patch, "Patch Changes"),
+            ] {
+                let entries = notes
+                    .iter()
+                    .filter(|(x, _)| *x == bump)
+                    .map(|(_, summary)| render_note(summary))
+                    .collect::<Vec<_>>();
+
+                if !entries.is_empty() {
+                    lines.push(format!("{} {}", heading, title));
+                    lines.push("".to_string());
+                    lines.extend(entries);
+                    lines.push("".to_string());
+                }
+            }
+
+            let breaking = commits.iter().filter(|x| x.breaking).collect::<Vec<_>>();
+            if !breaking.is_empty() {
+                lines.push(format!("{} Breaking Changes", heading));
+                lines.push("".to_string());
+                lines.extend(breaking.into_iter().map(|x| render_entry(x, links)));
+                lines.push("".to_string());
+            }
+
+            for section in SECTIONS {
+                let entries = commits
+                    .iter()
+                    .filter(|x| !x.breaking && x.section() == Some(section))
+                    .map(|x| render_entry(x, links))
+                    .collect::<Vec<_>>();
+
+                if !entries.is_empty() {
+                    lines.push(format!("{} {}", heading, section));
+                    lines.push("".to_string());
+                    lines.extend(entries);
+                    lines.push("".to_string());
+                }
+            }
+        }
+    }
+
+    if lines.is_empty() {
+        lines.push("- Version bump only".to_string());
+    }
+
+    while matches!(lines.last(), Some(x) if x.is_empty()) {
+        lines.pop();
+    }
+
+    lines
+}
+
+/// Insert a release section below the changelog header, creating the header if needed
+pub fn prepend_section(existing: Option<String>, section: &str) -> String {
+    let existing = existing.unwrap_or_default();
+    let existing = existing.trim_start();
+
+    let (header, rest) = if existing.starts_with("# ") {
+        let (header, rest) = existing.split_once('\n').unwrap_or((existing, ""));
+        (header.trim_end(), rest.trim_start())
+    } else {
+        (HEADER, existing)
+    };
+
+    if rest.is_empty() {
+        format!("{}\n\n{}\n", header, section)
+    } else {
+        format!("{}\n\n{}\n\n{}", header, section, rest)
+    }
+}
+
+fn write_section(path: &Utf8Path, section: &str, transaction: &mut Transaction) -> Result {
+    let existing = if path.is_file() {
+        Some(fs::read_to_string(path)?)
+    } else {
+        None
+    };
+
+    transaction.write(path, &prepend_section(existing, section))
+}
+
+/// Changelog of the crate, when changelogs are enabled for it
+pub fn changelog_path(config: &WorkspaceConfig, pkg: &Pkg) -> Utf8PathBuf {
+    let file = pkg.config.changelog.clone().unwrap_or_else(|| {
+        config
+            .changelog
+            .as_ref()
+            .and_then(|x| x.path.clone())
+            .unwrap_or_else(|| DEFAULT_CHANGELOG.to_string())
+    });
+
+    pkg.manifest_path.parent().expect(INTERNAL_ERR).join(file)
+}
+
+/// Prepend release sections to the changelogs of the versioned crates,
+/// returning the files that were written so they can be committed
+pub fn write_changelogs(
+    metadata: &Metadata,
+    config: &WorkspaceConfig,
+    git_opt: &GitOpt,
+    release: &Release,
+    transaction: &mut Transaction,
+) -> Result<Vec<Utf8PathBuf>> {
+    let Release {
+        since,
+        changesets,
+        new_version,
+        new_versions,
+        ..
+    } = release;
+
+    let enabled = config.changelog.is_some()
+        || new_versions
+            .values()
+            .any(|(pkg, _)| pkg.config.changelog.is_some());
+
+    if !enabled {
+        return Ok(vec![]);
+    }
+
+    let root = &metadata.workspace_root;
+
+    // Without git, the changelogs only have the notes of the changesets
+    let links = if git_opt.no_git {
+        Links(None)
+    } else {
+        Links::new(root, git_opt, config)?
+    };
+    let date = today();
+    let workspace_format = config
+        .changelog
+        .as_ref()
+        .and_then(|x| x.format)
+        .unwrap_or_default();
+
+    let member_paths = member_paths(metadata);
+
+    let mut written = vec![];
+    let mut root_sections = vec![];
+
+    for (name, (pkg, version)) in new_versions {
+        let notes = changesets.entries(name);
+        let format = pkg.config.changelog_format.unwrap_or(workspace_format);
+        let commits = if git_opt.no_git {
+            vec![]
+        } else {
+            pkg_commits(root, git_opt, &member_paths, name, pkg, since.as_deref())?
+        };
+
+        if config.changelog.is_some() || pkg.config.changelog.is_some() {
+            let file = changelog_path(config, pkg);
+
+            let mut section = vec![format!("## {} ({})", version, date), "".to_string()];
+            section.extend(render_entries(&notes, &commits, format, &links, 3));
+
+            write_section(&file, &section.join("\n"), transaction)?;
+            written.push(file);
+        }
+
+        let mut section = vec![format!("### {} {}", name, version), "".to_string()];
+        section.extend(render_entries(&notes, &commits, format, &links, 4));
+        root_sections.push(section.join("\n"));
+    }
+
+    if let Some(file) = config.changelog.as_ref().and_then(|x| x.root.as_ref()) {
+        let file = root.join(file);
+
+        let heading = match new_version {
+            Some(version) => format!("## {} ({})", version, date),
+            None => format!("## {}", date),
+        };
+
+        write_section(
+            &file,
+            &format!("{}\n\n{}", heading, root_sections.join("\n\n")),
+            transaction,
+        )?;
+        written.push(file);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_conventional() {
+        let c = Commit::parse("0123456789abcdef", "feat(core): add things", "");
+        assert_eq!(c.kind.as_deref(), Some("feat"));
+        assert_eq!(c.scope.as_deref(), Some("core"));
+        assert_eq!(c.subject, "add things");
+        assert!(!c.breaking);
+        assert_eq!(c.short_sha(), "0123456");
+    }
+
+    #[test]
+    fn test_parse_breaking_bang() {
+        let c = Commit::parse("0123456", "refactor!: drop old api", "");
+        assert_eq!(c.kind.as_deref(), Some("refactor"));
+        assert!(c.breaking);
+    }
+
+    #[test]
+    fn test_parse_breaking_footer() {
+        let c = Commit::parse("0123456", "fix: things", "BREAKING CHANGE: removed x");
+        assert!(c.breaking);
+    }
+
+    #[test]
+    fn test_parse_plain() {
+        let c = Commit::parse("0123456", "Update readme", "");
+        assert_eq!(c.kind, None);
+        assert_eq!(c.subject, "Update readme");
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let members = ["a", "a/b", "a/b/c", "ab", "d"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(nested_paths(&members, &["a"]), vec!["a/b", "a/b/c"]);
+        assert_eq!(nested_paths(&members, &["a", "a/b"]), vec!["a/b/c"]);
+        assert_eq!(nested_paths(&members, &["d"]), Vec::<String>::new());
+        assert_eq!(nested_paths(&members, &["."]).len(), 5);
+        assert_eq!(slash_path(Path::new("a").join("b")), "a/b");
+    }
+
+    #[test]
+    fn test_remote_web_url() {
+        assert_eq!(
+            remote_web_url("git@github.com:miraclx/cargo-workspaces.git").as_deref(),
+            Some("https://github.com/miraclx/cargo-workspaces")
+        );
+        assert_eq!(
+            remote_web_url("https://github.com/miraclx/cargo-workspaces").as_deref(),
+            Some("https://github.com/miraclx/cargo-workspaces")
+        );
+        assert_eq!(
+            remote_web_url("ssh://git@gitlab.com:22/a/b/c.git").as_deref(),
+            Some("https://gitlab.com/a/b/c")
+        );
+    }
+
+    #[test]
+    fn test_render_conventional() {
+        let commits = vec![
+            Commit::parse("1111111", "feat: one", ""),
+            Commit::parse("2222222", "fix(x): two", ""),
+            Commit::parse("3333333", "chore: three", ""),
+            Commit::parse("4444444", "feat!: four", ""),
+        ];
+
+        assert_eq!(
+            render_entries(
+                &[],
+                &commits,
+                ChangelogFormat::Conventional,
+                &Links(None),
+                3
+            )
+            .join("\n"),
+            indoc! {"
+                ### Breaking Changes
+
+                - four (`4444444`)
+
+                ### Features
+
+                - one (`1111111`)
+
+                ### Bug Fixes
+
+                - **x:** two (`2222222`)"
+            }
+        );
+    }
+
+    #[test]
+    fn test_render_empty() {
+        assert_eq!(
+            render_entries(&[], &[], ChangelogFormat::Conventional, &Links(None), 3),
+            vec!["- Version bump only"]
+        );
+    }
+
+    #[test]
+    fn test_render_notes() {
+        let commits = vec![Commit::parse("1111111", "fix: one", "")];
+        let notes = vec![
+            (&Bump::Patch, "Small fix"),
+            (&Bump::Minor, "New thing\nwith details"),
+        ];
+
+        assert_eq!(
+            render_entries(
+                &notes,
+                &commits,
+                ChangelogFormat::Conventional,
+                &Links(None),
+                3
+            )
+            .join("\n"),
+            indoc! {"
+                ### Minor Changes
+
+                - New thing
+                  with details
+
+                ### Patch Changes
+
+                - Small fix
+
+                ### Bug Fixes
+
+                - one (`1111111`)"
+            }
+        );
+    }
+
+    #[test]
+    fn test_prepend_section_new() {
+        assert_eq!(
+            prepend_section(None, "## 0.1.0\n\n- one"),
+            "# Changelog\n\n## 0.1.0\n\n- one\n"
+        );
+    }
+
+    #[test]
+    fn test_prepend_section_existing() {
+        let existing = indoc! {"
+            # Changelog
+
+            ## 0.1.0
+
+            - one
+        "};
+
+        assert_eq!(
+            prepend_section(Some(existing.to_string()), "## 0.2.0\n\n- two"),
+            indoc! {"
+                # Changelog
+
+                ## 0.2.0
+
+                - two
+
+                ## 0.1.0
+
+                - one
+            "}
+        );
+    }
+
+    #[test]
+    fn test_prepend_section_without_header() {
+        assert_eq!(
+            prepend_section(Some("## 0.1.0\n".to_string()), "## 0.2.0"),
+            "# Changelog\n\n## 0.2.0\n\n## 0.1.0\n"
+        );
+    }
+}
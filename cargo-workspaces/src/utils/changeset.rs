@@ -0,0 +1,350 @@
+use crate::utils::{Bump, Error, Result, Transaction};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::ArgEnum;
+
+use std::{
+    collections::{BTreeMap as Map, HashMap},
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub const CHANGESET_DIR: &str = ".changeset";
+
+const FENCE: &str = "---";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changeset {
+    pub path: Utf8PathBuf,
+    pub bumps: Map<String, Bump>,
+    pub summary: String,
+}
+
+impl Changeset {
+    pub fn parse(path: Utf8PathBuf, content: &str) -> Result<Self> {
+        let bad = |msg: &str| Error::BadChangeset {
+            path: path.to_string(),
+            msg: msg.to_string(),
+        };
+
+        let mut lines = content.lines();
+
+        if lines.next().map(str::trim) != Some(FENCE) {
+            return Err(bad("expected the file to start with `---`"));
+        }
+
+        let mut bumps = Map::new();
+
+        loop {
+            let line = match lines.next() {
+                Some(line) => line.trim(),
+                None => return Err(bad("unterminated header, expected a closing `---`")),
+            };
+
+            if line == FENCE {
+                break;
+            }
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, level) = line
+                .rsplit_once(':')
+                .ok_or_else(|| bad(&format!("expected `\"crate\": level`, found `{}`", line)))?;
+
+            let name = name.trim().trim_matches(|c| c == '"' || c == '\'');
+            let bump = parse_level(level.trim()).map_err(|msg| bad(&msg))?;
+
+            bumps.insert(name.to_string(), bump);
+        }
+
+        if bumps.is_empty() {
+            return Err(bad("no crates listed in the header"));
+        }
+
+        Ok(Self {
+            path,
+            bumps,
+            summary: lines.collect::<Vec<_>>().join("\n").trim().to_string(),
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut lines = vec![FENCE.to_string()];
+
+        for (name, bump) in &self.bumps {
+            lines.push(format!("\"{}\": {}", name, bump));
+        }
+
+        lines.push(FENCE.to_string());
+        lines.push("".to_string());
+        lines.push(self.summary.clone());
+
+        format!("{}\n", lines.join("\n"))
+    }
+}
+
+/// Parse a changeset bump level, only `major`, `minor` and `patch` are allowed
+pub fn parse_level(level: &str) -> std::result::Result<Bump, String> {
+    match Bump::from_str(level, true) {
+        Ok(bump @ (Bump::Major | Bump::Minor | Bump::Patch)) => Ok(bump),
+        _ => Err(format!(
+            "invalid bump level `{}`, expected one of major, minor, patch",
+            level
+        )),
+    }
+}
+
+fn max_bump(a: Bump, b: Bump) -> Bump {
+    if b.selected() > a.selected() {
+        b
+    } else {
+        a
+    }
+}
+
+/// Pending changesets in the workspace
+#[derive(Debug, Default)]
+pub struct Changesets(pub Vec<Changeset>);
+
+impl Changesets {
+    pub fn read(root: &Utf8Path) -> Result<Self> {
+        let dir = root.join(CHANGESET_DIR);
+
+        if !dir.is_dir() {
+            return Ok(Self::default());
+        }
+
+        let mut paths = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok())
+            .filter(|path| path.extension() == Some("md") && path.is_file())
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        let mut changesets = vec![];
+        for path in paths {
+            let content = fs::read_to_string(&path)?;
+            changesets.push(Changeset::parse(path, &content)?);
+        }
+
+        Ok(Self(changesets))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Highest bump requested for each crate
+    pub fn bumps(&self) -> HashMap<String, Bump> {
+        let mut bumps = HashMap::new();
+
+        for changeset in &self.0 {
+            for (name, bump) in &changeset.bumps {
+                let bump = match bumps.remove(name) {
+                    Some(prev) => max_bump(prev, bump.clone()),
+                    None => bump.clone(),
+                };
+                bumps.insert(name.clone(), bump);
+            }
+        }
+
+        bumps
+    }
+
+    /// Summaries of the changesets that name this crate
+    pub fn entries(&self, name: &str) -> Vec<(&Bump, &str)> {
+        self.0
+            .iter()
+            .filter_map(|x| x.bumps.get(name).map(|bump| (bump, x.summary.as_str())))
+            .collect()
+    }
+
+    pub fn validate(&self, names: &[&str]) -> Result {
+        for changeset in &self.0 {
+            for name in changeset.bumps.keys() {
+                if !names.contains(&name.as_str()) {
+                    return Err(Error::UnknownChangesetPackage {
+                        name: name.clone(),
+                        path: changeset.path.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the changesets whose crates were all released, and drop the released crates
+    /// from the others so the rest stay pending
+    pub fn consume<F>(&self, released: F, transaction: &mut Transaction) -> Result
+    where
+        F: Fn(&str) -> bool,
+    {
+        for changeset in &self.0 {
+            let pending = changeset
+                .bumps
+                .iter()
+                .filter(|(name, _)| !released(name))
+                .map(|(name, bump)| (name.clone(), bump.clone()))
+                .collect::<Map<_, _>>();
+
+            if pending.is_empty() {
+                transaction.remove(&changeset.path)?;
+            } else if pending.len() < changeset.bumps.len() {
+                let rest = Changeset {
+                    bumps: pending,
+                    ..changeset.clone()
+                };
+                transaction.write(&changeset.path, &rest.render())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A file name for a new changeset, based on its summary
+pub fn changeset_path(root: &Utf8Path, summary: &str) -> Utf8PathBuf {
+    let slug = summary
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .take(4)
+        .map(|x| x.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.subsec_nanos());
+
+    loop {
+        let id = match slug.as_str() {
+            "" => format!("{:06x}", seed & 0xffffff),
+            slug => format!("{}-{:06x}", slug, seed & 0xffffff),
+        };
+
+        let path = root.join(CHANGESET_DIR).join(format!("{}.md", id));
+        if !path.exists() {
+            return path;
+        }
+
+        seed = seed.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse() {
+        let c = Changeset::parse(
+            "a.md".into(),
+            indoc! {r#"
+                ---
+                "dep1": minor
+                dep2: patch
+                ---
+
+                Add the thing
+            "#},
+        )
+        .unwrap();
+
+        assert_eq!(c.bumps.get("dep1"), Some(&Bump::Minor));
+        assert_eq!(c.bumps.get("dep2"), Some(&Bump::Patch));
+        assert_eq!(c.summary, "Add the thing");
+    }
+
+    #[test]
+    fn test_parse_bad_level() {
+        let c = Changeset::parse("a.md".into(), "---\ndep1: custom\n---\n");
+        assert!(matches!(c, Err(Error::BadChangeset { .. })));
+    }
+
+    #[test]
+    fn test_parse_unterminated() {
+        let c = Changeset::parse("a.md".into(), "---\ndep1: major\n");
+        assert!(matches!(c, Err(Error::BadChangeset { .. })));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut bumps = Map::new();
+        bumps.insert("dep1".to_string(), Bump::Major);
+
+        let c = Changeset {
+            path: "a.md".into(),
+            bumps,
+            summary: "Breaking thing".to_string(),
+        };
+
+        assert_eq!(c.render(), "---\n\"dep1\": major\n---\n\nBreaking thing\n");
+        assert_eq!(Changeset::parse("a.md".into(), &c.render()).unwrap(), c);
+    }
+
+    #[test]
+    fn test_bumps() {
+        let c = Changesets(vec![
+            Changeset::parse("a.md".into(), "---\na: patch\nb: major\n---\n").unwrap(),
+            Changeset::parse("b.md".into(), "---\na: minor\nb: patch\n---\n").unwrap(),
+        ]);
+
+        let bumps = c.bumps();
+        assert_eq!(bumps.get("a"), Some(&Bump::Minor));
+        assert_eq!(bumps.get("b"), Some(&Bump::Major));
+    }
+
+    #[test]
+    fn test_consume() {
+        let dir = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join("cargo-workspaces-changeset-consume");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(dir.join("a.md"), "---\na: patch\n---\n\nOnly a\n").unwrap();
+        fs::write(dir.join("b.md"), "---\na: minor\nb: patch\n---\n\nBoth\n").unwrap();
+        fs::write(dir.join("c.md"), "---\nb: major\n---\n\nOnly b\n").unwrap();
+
+        let c = Changesets(
+            ["a.md", "b.md", "c.md"]
+                .iter()
+                .map(|x| {
+                    let path = dir.join(x);
+                    let content = fs::read_to_string(&path).unwrap();
+                    Changeset::parse(path, &content).unwrap()
+                })
+                .collect(),
+        );
+
+        let mut transaction = Transaction::default();
+        c.consume(|name| name == "a", &mut transaction).unwrap();
+
+        assert!(!dir.join("a.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("b.md")).unwrap(),
+            "---\n\"b\": patch\n---\n\nBoth\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("c.md")).unwrap(),
+            "---\nb: major\n---\n\nOnly b\n"
+        );
+
+        transaction.rollback().unwrap();
+        assert!(dir.join("a.md").exists());
+        assert!(fs::read_to_string(dir.join("b.md"))
+            .unwrap()
+            .contains("a: minor"));
+    }
+
+    #[test]
+    fn test_changeset_path() {
+        let path = changeset_path(Utf8Path::new("/ws"), "Fix the: Big bug, again!");
+        assert!(path.as_str().starts_with("/ws/.changeset/fix-the-big-bug-"));
+        assert_eq!(path.extension(), Some("md"));
+    }
+}
@@ -1,5 +1,8 @@
-use crate::utils;
+use crate::utils::{
+    self, CalverFormat, ChangelogFormat, GroupName, VersionFileSpec, DEFAULT_CALVER_FORMAT,
+};
 
+use globset::Glob;
 use semver::Version;
 use serde::{de, Deserialize};
 use serde_json::{from_value, Value};
@@ -11,7 +14,6 @@ struct MetadataWorkspaces<T> {
     pub workspaces: Option<T>,
 }
 
-// TODO: Validation of conflicting options (hard to tell conflicts if between cli and option)
 pub fn read_config<T>(value: &Value) -> utils::Result<T>
 where
     T: for<'de> Deserialize<'de> + Default,
@@ -24,6 +26,47 @@ where
 #[derive(Deserialize, Default, Debug, Clone, Ord, Eq, PartialOrd, PartialEq)]
 pub struct PackageConfig {
     pub independent: Option<bool>,
+    pub changelog: Option<String>,
+    pub changelog_format: Option<ChangelogFormat>,
+    pub sync: Option<Vec<String>>,
+    pub scheme: Option<VersionScheme>,
+    pub calver_format: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, Ord, Eq, PartialOrd, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionScheme {
+    /// Versions are picked with the semver bumps
+    #[default]
+    Semver,
+    /// Versions are computed from the release date, following the `calver_format`
+    Calver,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupPolicy {
+    /// Every crate is bumped together, even without changes
+    Fixed,
+    /// Only changed crates are bumped, always to the highest version in the group
+    #[default]
+    Linked,
+    /// Every crate is versioned on its own
+    Independent,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependentBump {
+    /// Dependents are never bumped for their dependencies
+    None,
+    /// Dependents are patch bumped whenever a dependency is bumped
+    Patch,
+    /// Dependents are bumped by the same level as their dependencies
+    Match,
+    /// Dependents are bumped when their requirement no longer matches the new version
+    #[default]
+    OutOfRange,
 }
 
 #[derive(Deserialize, Debug)]
@@ -31,10 +74,62 @@ pub struct PackageConfig {
 pub struct WorkspaceGroupSpec {
     pub name: String,
     pub version: Option<Version>,
+    pub policy: Option<GroupPolicy>,
+    pub scheme: Option<VersionScheme>,
+    pub calver_format: Option<String>,
     #[serde(deserialize_with = "deserialize_members")]
     pub members: Vec<GroupMember>,
 }
 
+/// The largest release a branch may produce
+#[derive(Deserialize, Debug, Clone, Copy, Ord, Eq, PartialOrd, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseLevel {
+    /// Only prereleases, of any level
+    Prerelease,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for ReleaseLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            ReleaseLevel::Prerelease => "prerelease",
+            ReleaseLevel::Patch => "patch",
+            ReleaseLevel::Minor => "minor",
+            ReleaseLevel::Major => "major",
+        })
+    }
+}
+
+impl ReleaseLevel {
+    /// The releases allowed at this level, like `patch releases`
+    pub fn releases(&self) -> String {
+        match self {
+            ReleaseLevel::Prerelease => "prereleases".to_string(),
+            level => format!("{} releases", level),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BranchPolicySpec {
+    /// Glob matching the branch names the policy applies to
+    pub name: String,
+    pub max_bump: ReleaseLevel,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ChangelogConfig {
+    pub path: Option<String>,
+    pub root: Option<String>,
+    pub format: Option<ChangelogFormat>,
+    pub commit_url: Option<String>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(transparent, deny_unknown_fields)]
 pub struct ExcludeSpec {
@@ -77,8 +172,117 @@ pub struct WorkspaceConfig {
     pub exclude: Option<ExcludeSpec>,
     #[serde(rename = "group", default)]
     pub groups: Vec<WorkspaceGroupSpec>,
-    pub allow_branch: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub allow_branch: Option<Vec<String>>,
+    #[serde(rename = "branch", default)]
+    pub branches: Vec<BranchPolicySpec>,
     pub no_individual_tags: Option<bool>,
+    pub no_git_commit: Option<bool>,
+    pub amend: Option<bool>,
+    pub message: Option<String>,
+    pub sign_git_commit: Option<bool>,
+    pub no_git_tag: Option<bool>,
+    pub no_global_tag: Option<bool>,
+    pub sign_git_tag: Option<bool>,
+    pub tag_private: Option<bool>,
+    pub tag_prefix: Option<String>,
+    pub individual_tag_prefix: Option<String>,
+    pub tag_msg: Option<Vec<String>>,
+    pub individual_tag_msg: Option<String>,
+    pub no_git_push: Option<bool>,
+    pub git_remote: Option<String>,
+    pub allow_dirty: Option<bool>,
+    pub no_git: Option<bool>,
+    pub include_merged_tags: Option<bool>,
+    pub force: Option<String>,
+    pub ignore_changes: Option<String>,
+    pub pre_id: Option<String>,
+    pub all: Option<bool>,
+    pub exact: Option<bool>,
+    pub yes: Option<bool>,
+    #[serde(rename = "groups")]
+    pub version_groups: Option<Vec<String>>,
+    pub no_pager: Option<bool>,
+    pub changelog: Option<ChangelogConfig>,
+    pub prerelease_channels: Option<Vec<String>>,
+    pub dependent_bump: Option<DependentBump>,
+    #[serde(rename = "version_file", default)]
+    pub version_files: Vec<VersionFileSpec>,
+}
+
+impl WorkspaceConfig {
+    /// The first branch policy whose glob matches the branch
+    pub fn branch_policy(&self, branch: &str) -> utils::Result<Option<&BranchPolicySpec>> {
+        for policy in &self.branches {
+            if Glob::new(&policy.name)?.compile_matcher().is_match(branch) {
+                return Ok(Some(policy));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn policy(&self, group: &GroupName) -> GroupPolicy {
+        self.explicit_policy(group).unwrap_or_default()
+    }
+
+    /// Policy set on the group in the config, `None` for the default group and groups without one
+    pub fn explicit_policy(&self, group: &GroupName) -> Option<GroupPolicy> {
+        match group {
+            GroupName::Custom(name) => self
+                .groups
+                .iter()
+                .find(|x| &x.name == name)
+                .and_then(|x| x.policy),
+            _ => None,
+        }
+    }
+
+    /// Calendar version format of the group, or of the crate when given, `None` under semver
+    pub fn calver(
+        &self,
+        group: &GroupName,
+        pkg: Option<&PackageConfig>,
+    ) -> utils::Result<Option<CalverFormat>> {
+        let group = match group {
+            GroupName::Custom(name) => self.groups.iter().find(|x| &x.name == name),
+            _ => None,
+        };
+
+        let scheme = pkg
+            .and_then(|x| x.scheme)
+            .or_else(|| group.and_then(|x| x.scheme))
+            .unwrap_or_default();
+
+        if scheme == VersionScheme::Semver {
+            return Ok(None);
+        }
+
+        let format = pkg
+            .and_then(|x| x.calver_format.as_deref())
+            .or_else(|| group.and_then(|x| x.calver_format.as_deref()))
+            .unwrap_or(DEFAULT_CALVER_FORMAT);
+
+        CalverFormat::parse(format).map(Some)
+    }
+}
+
+/// A single string or a list of them
+fn deserialize_one_or_many<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(Some(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => vec![one],
+        OneOrMany::Many(many) => many,
+    }))
 }
 
 fn deserialize_members<'de, D>(deserializer: D) -> Result<Vec<GroupMember>, D::Error>
@@ -109,3 +313,73 @@ where
     }
     deserializer.deserialize_seq(MembersVisitor)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_group_policy() {
+        let config: WorkspaceConfig = read_config(&json!({
+            "workspaces": {
+                "group": [
+                    { "name": "sdk", "members": ["sdk/*"], "policy": "fixed" },
+                    { "name": "utils", "members": ["utils/*"] },
+                ]
+            }
+        }))
+        .unwrap();
+
+        let group = |name: &str| GroupName::Custom(name.to_string());
+
+        assert_eq!(config.policy(&group("sdk")), GroupPolicy::Fixed);
+        assert_eq!(config.policy(&group("utils")), GroupPolicy::Linked);
+        assert_eq!(config.policy(&GroupName::Default), GroupPolicy::Linked);
+        assert_eq!(
+            config.explicit_policy(&group("sdk")),
+            Some(GroupPolicy::Fixed)
+        );
+        assert_eq!(config.explicit_policy(&group("utils")), None);
+        assert_eq!(config.explicit_policy(&GroupName::Default), None);
+    }
+
+    #[test]
+    fn test_dependent_bump() {
+        let config: WorkspaceConfig =
+            read_config(&json!({ "workspaces": { "dependent_bump": "out-of-range" } })).unwrap();
+
+        assert_eq!(config.dependent_bump, Some(DependentBump::OutOfRange));
+    }
+
+    #[test]
+    fn test_allow_branch() {
+        let config: WorkspaceConfig =
+            read_config(&json!({ "workspaces": { "allow_branch": "main" } })).unwrap();
+        assert_eq!(config.allow_branch, Some(vec!["main".to_string()]));
+
+        let config: WorkspaceConfig = read_config(&json!({
+            "workspaces": {
+                "allow_branch": ["main", "release/*"],
+                "branch": [{ "name": "release/*", "max_bump": "patch" }]
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            config.allow_branch,
+            Some(vec!["main".to_string(), "release/*".to_string()])
+        );
+        assert_eq!(config.branches[0].max_bump, ReleaseLevel::Patch);
+    }
+
+    #[test]
+    fn test_unknown_group_policy() {
+        let config = read_config::<WorkspaceConfig>(&json!({
+            "workspaces": {
+                "group": [{ "name": "sdk", "members": ["sdk/*"], "policy": "locked" }]
+            }
+        }));
+
+        assert!(config.is_err());
+    }
+}
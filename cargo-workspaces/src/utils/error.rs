@@ -12,6 +12,7 @@ use std::{
 
 lazy_static! {
     static ref DEBUG: AtomicBool = AtomicBool::new(false);
+    static ref NO_INTERACTIVE: AtomicBool = AtomicBool::new(false);
 }
 
 pub fn get_debug() -> bool {
@@ -22,6 +23,22 @@ pub fn set_debug() {
     DEBUG.store(true, Ordering::Relaxed);
 }
 
+pub fn set_no_interactive() {
+    NO_INTERACTIVE.store(true, Ordering::Relaxed);
+}
+
+/// Fail instead of prompting in non-interactive mode, naming what answers the prompt
+pub fn interactive(prompt: &str, answer: &str) -> Result<(), Error> {
+    if NO_INTERACTIVE.load(Ordering::Relaxed) {
+        return Err(Error::NotInteractive {
+            prompt: prompt.to_string(),
+            answer: answer.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
 macro_rules! _info {
     ($desc:expr, $val:expr) => {{
         oclif::term::TERM_ERR.write_line(&format!(
@@ -82,6 +99,10 @@ pub enum Error {
     ReservedGroupName { name: String },
     #[error("{msg}")]
     InvalidGroupName { msg: String },
+    #[error("the group `{name}` is independent and cannot have a version")]
+    IndependentGroupVersion { name: String },
+    #[error("the package `{name}` is independent but the group `{group}` is fixed")]
+    IndependentInFixedGroup { name: String, group: String },
     #[error(
         "these group member patterns matched no packages:\n{}",
         .0.iter().map(|(group_name, pkgs)| format!(
@@ -97,8 +118,64 @@ pub enum Error {
         .0.iter().map(|group| format!("`{}`", group)).collect::<Vec<_>>().join(", ")
     )]
     UnmatchedExcludeGroupPattern(HashSet<String>),
+    #[error("unable to find group {name}")]
+    GroupNotFound { name: String },
+    #[error("a bump for `{name}` was given multiple times")]
+    DuplicateBump { name: String },
+    #[error(
+        "the crates in the group `{group}` share a version but were given different bumps by: {}",
+        .sources.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    ConflictingBumps { group: String, sources: Vec<String> },
+    #[error("invalid changeset {path}: {msg}")]
+    BadChangeset { path: String, msg: String },
+    #[error("the changeset {path} names the package `{name}` which is not in the workspace")]
+    UnknownChangesetPackage { name: String, path: String },
+    #[error("a snapshot was already written, see {0}, restore it with `version --restore` first")]
+    SnapshotExists(String),
+    #[error("there is no snapshot to restore")]
+    NoSnapshot,
+    #[error("invalid calver format {format}: {msg}")]
+    BadCalverFormat { format: String, msg: String },
+    #[error(
+        "the calendar version {next} is not after the current version {version}, \
+         add `MICRO` to the format to release more than once in a period"
+    )]
+    CalverNotAfter { version: String, next: String },
+    #[error("invalid version file {path}: {msg}")]
+    BadVersionFile { path: String, msg: String },
+    #[error("unable to sync the version into {path}: {msg}")]
+    BadSyncFile { path: String, msg: String },
+    #[error("invalid release plan: {0}")]
+    BadPlan(String),
+    #[error("the release plan is out of date, {0}")]
+    StalePlan(String),
+    #[error(
+        "the release plan would create the tags {planned:?}, but these options create {actual:?}"
+    )]
+    PlanTagsMismatch {
+        planned: Vec<String>,
+        actual: Vec<String>,
+    },
+    #[error("invalid prerelease state: {0}")]
+    BadPreState(String),
+    #[error("already in prerelease mode with tag {tag}, exit it first")]
+    AlreadyInPreMode { tag: String },
+    #[error("not in prerelease mode")]
+    NotInPreMode,
+    #[error(
+        "unknown prerelease channel `{name}`, expected one of: {}",
+        .channels.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    UnknownChannel { name: String, channels: Vec<String> },
+    #[error("cannot promote from `{from}` to `{to}` which does not come after it")]
+    BackwardPromotion { from: String, to: String },
     #[error("package {0}'s manifest has no parent directory")]
     ManifestHasNoParent(String),
+    #[error("`{0}` and `{1}` cannot both be set in the workspace config")]
+    ConflictingConfig(String, String),
+    #[error("invalid value for `{key}` in the workspace config: {msg}")]
+    BadConfigValue { key: String, msg: String },
     #[error("unable to read metadata specified in Cargo.toml: {0}")]
     BadMetadata(serde_json::Error),
 
@@ -110,6 +187,10 @@ pub enum Error {
     PublishTimeout,
     #[error("unable to update Cargo.lock")]
     Update,
+    #[error("cannot ask \"{prompt}\" in non-interactive mode, answer it with {answer}")]
+    NotInteractive { prompt: String, answer: String },
+    #[error("unable to restore {path} while rolling back the release, got {err}")]
+    Rollback { path: String, err: io::Error },
 
     #[error("{0} value must contain '%n'")]
     MustContainPercentN(String),
@@ -126,6 +207,8 @@ pub enum Error {
     Cargo { err: io::Error, args: Vec<String> },
     #[error("unable to run git command with args {args:?}, got {err}")]
     Git { err: io::Error, args: Vec<String> },
+    #[error("git {args} failed: {err}")]
+    GitFailed { args: String, err: String },
 
     #[error("child command failed to exit successfully")]
     Bail,
@@ -140,18 +223,73 @@ pub enum Error {
     NoRemote { remote: String },
     #[error("local branch {branch} is behind upstream {upstream}")]
     BehindRemote { upstream: String, branch: String },
-    #[error("not allowed to run on branch {branch} because it doesn't match pattern {pattern}")]
-    BranchNotAllowed { branch: String, pattern: String },
+    #[error("not allowed to run on branch {branch} because it doesn't match any of the patterns {patterns}")]
+    BranchNotAllowed { branch: String, patterns: String },
+    #[error(
+        "the branch {branch} is limited to {limit}, but {name} would be released as {version}"
+    )]
+    BranchPolicy {
+        branch: String,
+        limit: String,
+        name: String,
+        version: String,
+    },
     #[error("unable to add files to git index, out = {0}, err = {1}")]
     NotAdded(String, String),
     #[error("unable to commit to git, out = {0}, err = {1}")]
     NotCommitted(String, String),
     #[error("unable to tag {0}, out = {1}, err = {2}")]
     NotTagged(String, String, String),
+    #[error("unable to sign the version commit, check the signing key set up in git: {0}")]
+    CommitNotSigned(String),
+    #[error("unable to sign the tag {0}, check the signing key set up in git: {1}")]
+    TagNotSigned(String, String),
     #[error("unterminated tag message scope")]
     UnterminatedTagMsgScope(String),
     #[error("unable to push to remote, out = {0}, err = {1}")]
     NotPushed(String, String),
+    #[error(
+        "unable to push to remote, landed on the remote: {}, not landed: {}, err = {err}",
+        refs_list(.landed),
+        refs_list(.missing)
+    )]
+    PushFailed {
+        landed: Vec<String>,
+        missing: Vec<String>,
+        err: String,
+    },
+    #[error("pushed to remote, but these refs are not on it: {}", refs_list(.0))]
+    RefsNotOnRemote(Vec<String>),
+    #[error(
+        "the working tree has uncommitted changes, commit or stash them or pass `--allow-dirty`: {}",
+        .0.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    UncommittedChanges(Vec<String>),
+    #[error(
+        "the member crates have untracked files, commit or ignore them or pass `--allow-dirty`: {}",
+        .0.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    UntrackedFiles(Vec<String>),
+    #[error("local branch {branch} has {count} commits not pushed to {upstream}, push them or pass `--allow-dirty`")]
+    UnpushedCommits {
+        branch: String,
+        upstream: String,
+        count: String,
+    },
+    #[error("the last commit was not created by cargo-workspaces")]
+    NotReleaseCommit,
+    #[error(
+        "the release commit also changes files unrelated to the release: {}",
+        .0.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    UnrelatedChanges(Vec<String>),
+    #[error(
+        "the release commit was already pushed to {}, revert it instead",
+        .0.iter().map(|x| format!("`{}`", x)).collect::<Vec<_>>().join(", ")
+    )]
+    ReleasePushed(Vec<String>),
+    #[error("unable to reset the release commit, out = {0}, err = {1}")]
+    NotReset(String, String),
 
     #[error("could not understand 'cargo config get' output: {0}")]
     BadConfigGetOutput(String),
@@ -172,6 +310,18 @@ pub enum Error {
     FromUtf8(#[from] std::string::FromUtf8Error),
 }
 
+/// Refs as a list for error messages, `none` when empty
+fn refs_list(refs: &[String]) -> String {
+    if refs.is_empty() {
+        return "none".to_string();
+    }
+
+    refs.iter()
+        .map(|x| format!("`{}`", x))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl CliError for Error {
     fn color(self) -> Self {
         match self {
@@ -182,6 +332,105 @@ impl CliError for Error {
             Self::PackageNotFound { id } => Self::PackageNotFound {
                 id: format!("{}", ERR_YELLOW.apply_to(id)),
             },
+            Self::GroupNotFound { name } => Self::GroupNotFound {
+                name: format!("{}", ERR_YELLOW.apply_to(name)),
+            },
+            Self::IndependentGroupVersion { name } => Self::IndependentGroupVersion {
+                name: format!("{}", ERR_YELLOW.apply_to(name)),
+            },
+            Self::IndependentInFixedGroup { name, group } => Self::IndependentInFixedGroup {
+                name: format!("{}", ERR_YELLOW.apply_to(name)),
+                group: format!("{}", ERR_YELLOW.apply_to(group)),
+            },
+            Self::BadChangeset { path, msg } => Self::BadChangeset {
+                path: format!("{}", ERR_YELLOW.apply_to(path)),
+                msg,
+            },
+            Self::UnknownChangesetPackage { name, path } => Self::UnknownChangesetPackage {
+                name: format!("{}", ERR_YELLOW.apply_to(name)),
+                path: format!("{}", ERR_YELLOW.apply_to(path)),
+            },
+            Self::NotInteractive { prompt, answer } => Self::NotInteractive {
+                prompt: format!("{}", ERR_YELLOW.apply_to(prompt)),
+                answer,
+            },
+            Self::UncommittedChanges(files) => Self::UncommittedChanges(
+                files
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+            ),
+            Self::UntrackedFiles(files) => Self::UntrackedFiles(
+                files
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+            ),
+            Self::UnpushedCommits {
+                branch,
+                upstream,
+                count,
+            } => Self::UnpushedCommits {
+                branch: format!("{}", ERR_YELLOW.apply_to(branch)),
+                upstream: format!("{}", ERR_YELLOW.apply_to(upstream)),
+                count,
+            },
+            Self::PushFailed {
+                landed,
+                missing,
+                err,
+            } => Self::PushFailed {
+                landed: landed
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+                missing: missing
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+                err,
+            },
+            Self::RefsNotOnRemote(refs) => Self::RefsNotOnRemote(
+                refs.into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+            ),
+            Self::UnrelatedChanges(files) => Self::UnrelatedChanges(
+                files
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+            ),
+            Self::ReleasePushed(branches) => Self::ReleasePushed(
+                branches
+                    .into_iter()
+                    .map(|x| format!("{}", ERR_YELLOW.apply_to(x)))
+                    .collect(),
+            ),
+            Self::ConflictingConfig(a, b) => Self::ConflictingConfig(
+                format!("{}", ERR_YELLOW.apply_to(a)),
+                format!("{}", ERR_YELLOW.apply_to(b)),
+            ),
+            Self::BadConfigValue { key, msg } => Self::BadConfigValue {
+                key: format!("{}", ERR_YELLOW.apply_to(key)),
+                msg,
+            },
+            Self::BadCalverFormat { format, msg } => Self::BadCalverFormat {
+                format: format!("{}", ERR_YELLOW.apply_to(format)),
+                msg,
+            },
+            Self::CalverNotAfter { version, next } => Self::CalverNotAfter {
+                version: format!("{}", ERR_YELLOW.apply_to(version)),
+                next: format!("{}", ERR_YELLOW.apply_to(next)),
+            },
+            Self::BadVersionFile { path, msg } => Self::BadVersionFile {
+                path: format!("{}", ERR_YELLOW.apply_to(path)),
+                msg,
+            },
+            Self::BadSyncFile { path, msg } => Self::BadSyncFile {
+                path: format!("{}", ERR_YELLOW.apply_to(path)),
+                msg,
+            },
             Self::Verify(pkg) => Self::Verify(format!("{}", ERR_YELLOW.apply_to(pkg))),
             Self::Publish(pkg) => Self::Publish(format!("{}", ERR_YELLOW.apply_to(pkg))),
             Self::MustContainPercentN(val) => {
@@ -197,10 +446,24 @@ impl CliError for Error {
                 upstream: format!("{}", ERR_YELLOW.apply_to(upstream)),
                 branch: format!("{}", ERR_YELLOW.apply_to(branch)),
             },
-            Self::BranchNotAllowed { branch, pattern } => Self::BranchNotAllowed {
+            Self::BranchNotAllowed { branch, patterns } => Self::BranchNotAllowed {
                 branch: format!("{}", ERR_YELLOW.apply_to(branch)),
-                pattern: format!("{}", ERR_YELLOW.apply_to(pattern)),
+                patterns: format!("{}", ERR_YELLOW.apply_to(patterns)),
             },
+            Self::BranchPolicy {
+                branch,
+                limit,
+                name,
+                version,
+            } => Self::BranchPolicy {
+                branch: format!("{}", ERR_YELLOW.apply_to(branch)),
+                limit,
+                name: format!("{}", ERR_YELLOW.apply_to(name)),
+                version: format!("{}", ERR_YELLOW.apply_to(version)),
+            },
+            Self::TagNotSigned(tag, err) => {
+                Self::TagNotSigned(format!("{}", ERR_YELLOW.apply_to(tag)), err)
+            }
             Self::NotTagged(tag, out, err) => {
                 Self::NotTagged(format!("{}", ERR_YELLOW.apply_to(tag)), out, err)
             }
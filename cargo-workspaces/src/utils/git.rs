@@ -1,6 +1,10 @@
-use crate::utils::{debug, info, validate_value_containing_name, Error, Pkg, WorkspaceConfig};
+use crate::utils::{
+    debug, info, member_paths, validate_value_containing_name, BranchPolicySpec, Error, Pkg,
+    WorkspaceConfig,
+};
 
 use camino::Utf8PathBuf;
+use cargo_metadata::Metadata;
 use clap::Parser;
 use globset::Glob;
 use oclif::term::ERR_YELLOW;
@@ -11,6 +15,8 @@ use std::{
     process::{Command, ExitStatus},
 };
 
+pub(crate) const TRAILER: &str = "Generated by cargo-workspaces";
+
 pub fn git<'a>(
     root: &Utf8PathBuf,
     args: &[&'a str],
@@ -33,16 +39,64 @@ pub fn git<'a>(
     ))
 }
 
+const NO_GIT_COMMIT_CONFLICTS: &[&str] = &["amend", "message", "allow-branch", "sign-git-commit"];
+const MESSAGE_CONFLICTS: &[&str] = &["amend"];
+const NO_GIT_TAG_CONFLICTS: &[&str] = &[
+    "tag-msg",
+    "tag-prefix",
+    "tag-private",
+    "individual-tag-prefix",
+    "individual-tag-msg",
+    "no-individual-tags",
+    "no-global-tag",
+    "sign-git-tag",
+];
+const NO_INDIVIDUAL_TAGS_CONFLICTS: &[&str] = &["individual-tag-prefix"];
+const NO_GIT_PUSH_CONFLICTS: &[&str] = &["git-remote"];
+const NO_GIT_CONFLICTS: &[&str] = &[
+    "no-git-commit",
+    "allow-branch",
+    "amend",
+    "message",
+    "no-git-tag",
+    "no-individual-tags",
+    "no-global-tag",
+    "tag-private",
+    "tag-prefix",
+    "individual-tag-prefix",
+    "tag-msg",
+    "individual-tag-msg",
+    "no-git-push",
+    "git-remote",
+    "sign-git-commit",
+    "sign-git-tag",
+];
+
+/// Options that can't be used together, whether given on the command line or in the config
+const CONFLICTS: &[(&str, &[&str])] = &[
+    ("no-git-commit", NO_GIT_COMMIT_CONFLICTS),
+    ("message", MESSAGE_CONFLICTS),
+    ("no-git-tag", NO_GIT_TAG_CONFLICTS),
+    ("no-individual-tags", NO_INDIVIDUAL_TAGS_CONFLICTS),
+    ("no-git-push", NO_GIT_PUSH_CONFLICTS),
+    ("no-git", NO_GIT_CONFLICTS),
+];
+
 #[derive(Debug, Parser)]
 #[clap(next_help_heading = "GIT OPTIONS")]
 pub struct GitOpt {
     /// Do not commit version changes, omitting this will tag the current commit
-    #[clap(long, conflicts_with_all = &["amend", "message", "allow-branch"])]
+    #[clap(long, conflicts_with_all = NO_GIT_COMMIT_CONFLICTS)]
     pub no_git_commit: bool,
 
-    /// Specify which branches to allow from [default: master]
-    #[clap(long, value_name = "pattern", forbid_empty_values(true))]
-    pub allow_branch: Option<String>,
+    /// Specify which branches to allow from, can be given multiple times [default: master]
+    #[clap(
+        long,
+        value_name = "pattern",
+        multiple_occurrences = true,
+        forbid_empty_values(true)
+    )]
+    pub allow_branch: Vec<String>,
 
     /// Amend the existing commit, instead of generating a new one
     #[clap(long)]
@@ -52,40 +106,47 @@ pub struct GitOpt {
     #[clap(
         short,
         long,
-        conflicts_with_all = &["amend"],
+        conflicts_with_all = MESSAGE_CONFLICTS,
         forbid_empty_values(true)
     )]
     pub message: Option<String>,
 
+    /// Sign the version commit with the GPG or SSH key configured in git
+    #[clap(long)]
+    pub sign_git_commit: bool,
+
     /// Do not tag generated commit (implies --no-individual-tags and --no-global-tag)
-    #[clap(long, conflicts_with_all = &["tag-msg", "tag-prefix", "tag-private", "individual-tag-prefix", "individual-tag-msg", "no-individual-tags", "no-global-tag"])]
+    #[clap(long, conflicts_with_all = NO_GIT_TAG_CONFLICTS)]
     pub no_git_tag: bool,
 
     /// Do not tag individual versions for crates
-    #[clap(long, conflicts_with_all = &["individual-tag-prefix"])]
+    #[clap(long, conflicts_with_all = NO_INDIVIDUAL_TAGS_CONFLICTS)]
     pub no_individual_tags: bool,
 
     /// Do not create a global tag for a workspace
     #[clap(long)]
     pub no_global_tag: bool,
 
+    /// Sign the tags with the GPG or SSH key configured in git
+    #[clap(long)]
+    pub sign_git_tag: bool,
+
     /// Also tag individual versions of private packages
     #[clap(long)]
     pub tag_private: bool,
 
-    /// Customize tag prefix for global tags (can be empty)
-    #[clap(long, default_value = "v", value_name = "prefix")]
-    pub tag_prefix: String,
+    /// Customize tag prefix for global tags (can be empty) [default: v]
+    #[clap(long, value_name = "prefix")]
+    tag_prefix: Option<String>,
 
-    /// Customize prefix for individual tags (should contain `%n`)
+    /// Customize prefix for individual tags (should contain `%n`) [default: %n@]
     #[clap(
         long,
-        default_value = "%n@",
         value_name = "prefix",
         validator = validate_value_containing_name,
         forbid_empty_values(true)
     )]
-    pub individual_tag_prefix: String,
+    individual_tag_prefix: Option<String>,
 
     /// Customize tag msg, defaults to tag name (can contain `%v`)
     #[clap(long, value_name = "msg", multiple_occurrences = true)]
@@ -96,38 +157,203 @@ pub struct GitOpt {
     pub individual_tag_msg: Option<String>,
 
     /// Do not push generated commit and tags to git remote
-    #[clap(long, conflicts_with_all = &["git-remote"])]
+    #[clap(long, conflicts_with_all = NO_GIT_PUSH_CONFLICTS)]
     pub no_git_push: bool,
 
-    /// Push git changes to the specified remote
-    #[clap(
-        long,
-        default_value = "origin",
-        value_name = "remote",
-        forbid_empty_values(true)
-    )]
-    pub git_remote: String,
+    /// Push git changes to the specified remote [default: origin]
+    #[clap(long, value_name = "remote", forbid_empty_values(true))]
+    git_remote: Option<String>,
+
+    /// Allow uncommitted changes, untracked files in the crates and unpushed commits
+    #[clap(long)]
+    pub allow_dirty: bool,
 
     /// Do not perform any git operations (implies --no-git-commit and --no-git-tag)
-    #[clap(long, conflicts_with_all = &[
-        "no-git-commit", "allow-branch", "amend", "message",
-        "no-git-tag", "no-individual-tags", "no-global-tag",
-        "tag-private", "tag-prefix", "individual-tag-prefix",
-        "tag-msg", "individual-tag-msg", "no-git-push", "git-remote"
-    ])]
+    #[clap(long, conflicts_with_all = NO_GIT_CONFLICTS)]
     pub no_git: bool,
 }
 
 impl GitOpt {
-    pub fn validate(
+    /// Fill in the options missing from the command line with the ones in the workspace
+    /// config, leaving out the configured options that conflict with the given ones
+    pub fn configure(&mut self, config: &WorkspaceConfig) -> Result<(), Error> {
+        let configured = Self::from_config(config)?;
+        let (given, set) = (self.given(), configured.given());
+
+        for (name, conflicts) in CONFLICTS {
+            if !set.contains(name) {
+                continue;
+            }
+
+            if let Some(other) = conflicts.iter().find(|x| set.contains(x)) {
+                return Err(Error::ConflictingConfig(
+                    name.replace('-', "_"),
+                    other.replace('-', "_"),
+                ));
+            }
+        }
+
+        let keep = |name: &str| !given.iter().any(|x| conflicting(x, name));
+
+        let Self {
+            no_git_commit,
+            allow_branch,
+            amend,
+            message,
+            sign_git_commit,
+            no_git_tag,
+            no_individual_tags,
+            no_global_tag,
+            sign_git_tag,
+            tag_private,
+            tag_prefix,
+            individual_tag_prefix,
+            tag_msg,
+            individual_tag_msg,
+            no_git_push,
+            git_remote,
+            allow_dirty,
+            no_git,
+        } = configured;
+
+        self.no_git_commit |= no_git_commit && keep("no-git-commit");
+        self.amend |= amend && keep("amend");
+        self.sign_git_commit |= sign_git_commit && keep("sign-git-commit");
+        self.no_git_tag |= no_git_tag && keep("no-git-tag");
+        self.no_individual_tags |= no_individual_tags && keep("no-individual-tags");
+        self.no_global_tag |= no_global_tag && keep("no-global-tag");
+        self.sign_git_tag |= sign_git_tag && keep("sign-git-tag");
+        self.tag_private |= tag_private && keep("tag-private");
+        self.no_git_push |= no_git_push && keep("no-git-push");
+        self.allow_dirty |= allow_dirty && keep("allow-dirty");
+        self.no_git |= no_git && keep("no-git");
+
+        if self.allow_branch.is_empty() && keep("allow-branch") {
+            self.allow_branch = allow_branch;
+        }
+        if self.message.is_none() && keep("message") {
+            self.message = message;
+        }
+        if self.tag_prefix.is_none() && keep("tag-prefix") {
+            self.tag_prefix = tag_prefix;
+        }
+        if self.individual_tag_prefix.is_none() && keep("individual-tag-prefix") {
+            self.individual_tag_prefix = individual_tag_prefix;
+        }
+        if self.tag_msg.is_empty() && keep("tag-msg") {
+            self.tag_msg = tag_msg;
+        }
+        if self.individual_tag_msg.is_none() && keep("individual-tag-msg") {
+            self.individual_tag_msg = individual_tag_msg;
+        }
+        if self.git_remote.is_none() && keep("git-remote") {
+            self.git_remote = git_remote;
+        }
+
+        Ok(())
+    }
+
+    fn from_config(config: &WorkspaceConfig) -> Result<Self, Error> {
+        if let Some(prefix) = &config.individual_tag_prefix {
+            validate_value_containing_name(prefix).map_err(|msg| Error::BadConfigValue {
+                key: "individual_tag_prefix".to_string(),
+                msg: msg.trim().to_string(),
+            })?;
+        }
+
+        Ok(Self {
+            no_git_commit: config.no_git_commit.unwrap_or_default(),
+            allow_branch: config.allow_branch.clone().unwrap_or_default(),
+            amend: config.amend.unwrap_or_default(),
+            message: config.message.clone(),
+            sign_git_commit: config.sign_git_commit.unwrap_or_default(),
+            no_git_tag: config.no_git_tag.unwrap_or_default(),
+            no_individual_tags: config.no_individual_tags.unwrap_or_default(),
+            no_global_tag: config.no_global_tag.unwrap_or_default(),
+            sign_git_tag: config.sign_git_tag.unwrap_or_default(),
+            tag_private: config.tag_private.unwrap_or_default(),
+            tag_prefix: config.tag_prefix.clone(),
+            individual_tag_prefix: config.individual_tag_prefix.clone(),
+            tag_msg: config.tag_msg.clone().unwrap_or_default(),
+            individual_tag_msg: config.individual_tag_msg.clone(),
+            no_git_push: config.no_git_push.unwrap_or_default(),
+            git_remote: config.git_remote.clone(),
+            allow_dirty: config.allow_dirty.unwrap_or_default(),
+            no_git: config.no_git.unwrap_or_default(),
+        })
+    }
+
+    /// Names of the options that are set
+    fn given(&self) -> Vec<&'static str> {
+        [
+            ("no-git-commit", self.no_git_commit),
+            ("allow-branch", !self.allow_branch.is_empty()),
+            ("amend", self.amend),
+            ("message", self.message.is_some()),
+            ("sign-git-commit", self.sign_git_commit),
+            ("no-git-tag", self.no_git_tag),
+            ("no-individual-tags", self.no_individual_tags),
+            ("no-global-tag", self.no_global_tag),
+            ("sign-git-tag", self.sign_git_tag),
+            ("tag-private", self.tag_private),
+            ("tag-prefix", self.tag_prefix.is_some()),
+            (
+                "individual-tag-prefix",
+                self.individual_tag_prefix.is_some(),
+            ),
+            ("tag-msg", !self.tag_msg.is_empty()),
+            ("individual-tag-msg", self.individual_tag_msg.is_some()),
+            ("no-git-push", self.no_git_push),
+            ("git-remote", self.git_remote.is_some()),
+            ("allow-dirty", self.allow_dirty),
+            ("no-git", self.no_git),
+        ]
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect()
+    }
+
+    /// The current branch and the policy limiting the releases made from it
+    pub fn branch_policy<'a>(
         &self,
         root: &Utf8PathBuf,
+        config: &'a WorkspaceConfig,
+    ) -> Result<Option<(String, &'a BranchPolicySpec)>, Error> {
+        if self.no_git || config.branches.is_empty() {
+            return Ok(None);
+        }
+
+        let (_, branch, _) = git(root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+
+        Ok(config
+            .branch_policy(&branch)?
+            .map(|policy| (branch, policy)))
+    }
+
+    fn tag_prefix(&self) -> &str {
+        self.tag_prefix.as_deref().unwrap_or("v")
+    }
+
+    fn individual_tag_prefix(&self) -> &str {
+        self.individual_tag_prefix.as_deref().unwrap_or("%n@")
+    }
+
+    pub fn git_remote(&self) -> &str {
+        self.git_remote.as_deref().unwrap_or("origin")
+    }
+
+    pub fn validate(
+        &self,
+        metadata: &Metadata,
         config: &WorkspaceConfig,
     ) -> Result<Option<String>, Error> {
         if self.no_git {
             return Ok(None);
         }
 
+        let root = &metadata.workspace_root;
+
         let (_, out, err) = git(root, &["rev-list", "--count", "--all", "--max-count=1"])?;
 
         if err.contains("not a git repository") {
@@ -138,6 +364,25 @@ impl GitOpt {
             return Err(Error::NoCommits);
         }
 
+        if !self.allow_dirty {
+            check_clean(metadata)?;
+        }
+
+        // The limit itself is enforced as the versions are picked
+        if let Some((branch, policy)) = self.branch_policy(root, config)? {
+            info!(
+                "branch",
+                format!("{} only allows {}", branch, policy.max_bump.releases())
+            );
+        }
+
+        let (_, branch, _) = git(root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+
+        // Unpushed commits are refused even when the release itself isn't pushed
+        if !self.allow_dirty && branch != "HEAD" {
+            self.check_remote(root, &branch)?;
+        }
+
         if self.no_git_push
             || (self.no_git_commit
                 && (self.no_git_tag || (self.no_global_tag && self.no_individual_tags)))
@@ -150,18 +395,16 @@ impl GitOpt {
             &[
                 "for-each-ref",
                 "--format='%(refname)'",
-                &format!("refs/remotes/{}", self.git_remote),
+                &format!("refs/remotes/{}", self.git_remote()),
             ],
         )?;
 
         if out.is_empty() {
             return Err(Error::NoRemote {
-                remote: self.git_remote.clone(),
+                remote: self.git_remote().to_string(),
             });
         }
 
-        let (_, branch, _) = git(root, &["rev-parse", "--abbrev-ref", "HEAD"])?;
-
         if branch == "HEAD" {
             if self.no_git_commit {
                 return Ok(None);
@@ -169,53 +412,84 @@ impl GitOpt {
             return Err(Error::NotBranch);
         }
 
-        // Get the final `allow_branch` value
-        let allow_branch_default_value = String::from("master");
-        let allow_branch = self.allow_branch.as_ref().unwrap_or_else(|| {
-            config
-                .allow_branch
-                .as_ref()
-                .unwrap_or(&allow_branch_default_value)
-        });
-
-        // Treat `main` as `master`
-        let test_branch = if branch == "main" && allow_branch.as_str() == "master" {
-            "master".into()
+        let default_allow_branch = ["master".to_string()];
+        let allow_branch = if self.allow_branch.is_empty() {
+            &default_allow_branch[..]
         } else {
-            branch.clone()
+            &self.allow_branch[..]
         };
 
-        let pattern = Glob::new(&allow_branch)?;
+        let mut allowed = false;
+        for pattern in allow_branch {
+            // Treat `main` as `master`
+            let test_branch = if branch == "main" && pattern == "master" {
+                "master"
+            } else {
+                &branch
+            };
+
+            if Glob::new(pattern)?.compile_matcher().is_match(test_branch) {
+                allowed = true;
+                break;
+            }
+        }
 
-        if !pattern.compile_matcher().is_match(&test_branch) {
+        if !allowed {
             return Err(Error::BranchNotAllowed {
                 branch,
-                pattern: pattern.glob().to_string(),
+                patterns: allow_branch.join(", "),
             });
         }
 
-        git(root, &["remote", "update", &self.git_remote])?;
+        // Pushing still needs the branch to be up to date, even with unpushed commits allowed
+        if self.allow_dirty {
+            self.check_remote(root, &branch)?;
+        }
 
-        let remote_branch = format!("{}/{}", self.git_remote, branch);
+        return Ok(Some(branch));
+    }
 
-        let (_, out, _) = git(
+    /// Refuse a branch behind its remote branch, and with `--allow-dirty` not given, one with
+    /// commits that weren't pushed to it. Branches without a remote branch aren't checked
+    fn check_remote(&self, root: &Utf8PathBuf, branch: &str) -> Result<(), Error> {
+        git(root, &["remote", "update", self.git_remote()])?;
+
+        let remote_branch = format!("{}/{}", self.git_remote(), branch);
+
+        let (exists, _, _) = git(
             root,
             &[
-                "rev-list",
-                "--left-only",
-                "--count",
-                &format!("{}...{}", remote_branch, branch),
+                "rev-parse",
+                "-q",
+                "--verify",
+                &format!("refs/remotes/{}", remote_branch),
             ],
         )?;
 
-        if out != "0" {
+        if !exists.success() {
+            return Ok(());
+        }
+
+        let range = format!("{}...{}", remote_branch, branch);
+
+        if git_out(root, &["rev-list", "--left-only", "--count", &range])? != "0" {
             return Err(Error::BehindRemote {
-                branch,
+                branch: branch.to_string(),
                 upstream: remote_branch,
             });
         }
 
-        return Ok(Some(branch));
+        let out = git_out(root, &["rev-list", "--right-only", "--count", &range])?;
+
+        if out != "0" && !self.allow_dirty {
+            return Err(Error::UnpushedCommits {
+                branch: branch.to_string(),
+                upstream: remote_branch,
+                count: out,
+            });
+        }
+
+        Ok(())
     }
 
     pub fn commit(
@@ -223,6 +497,7 @@ impl GitOpt {
         root: &Utf8PathBuf,
         new_version: &Option<Version>,
         new_versions: &Map<String, (Pkg, Version)>,
+        files: &[Utf8PathBuf],
     ) -> Result<(), Error> {
         if self.no_git || self.no_git_commit {
             return Ok(());
@@ -236,39 +511,114 @@ impl GitOpt {
             return Err(Error::NotAdded(added.1, added.2));
         }
 
+        if !files.is_empty() {
+            let mut args = vec!["add", "--"];
+            args.extend(files.iter().map(|x| x.as_str()));
+
+            let added = git(root, &args)?;
+
+            if !added.0.success() {
+                return Err(Error::NotAdded(added.1, added.2));
+            }
+        }
+
         let mut args = vec!["commit".to_string()];
 
-        if self.amend {
+        if let Some(msg) = self.commit_message(new_version, new_versions) {
+            args.push("-m".to_string());
+            args.push(msg);
+        } else {
             args.push("--amend".to_string());
             args.push("--no-edit".to_string());
-        } else {
-            args.push("-m".to_string());
+        }
+
+        if self.sign_git_commit {
+            args.push("-S".to_string());
+        }
 
-            let mut msg = "Release %v";
+        let committed = git(root, &args.iter().map(|x| x.as_str()).collect::<Vec<_>>())?;
 
-            if let Some(supplied) = &self.message {
-                msg = supplied;
+        if !committed.0.success() {
+            if self.sign_git_commit && signing_failed(&committed.2) {
+                return Err(Error::CommitNotSigned(committed.2));
             }
+            return Err(Error::NotCommitted(committed.1, committed.2));
+        }
+
+        Ok(())
+    }
+
+    /// Tree of the index before the release stages anything, `None` when not committing
+    pub fn index_tree(&self, root: &Utf8PathBuf) -> Result<Option<String>, Error> {
+        if self.no_git || self.no_git_commit {
+            return Ok(None);
+        }
+
+        Ok(Some(git_out(root, &["write-tree"])?))
+    }
+
+    /// Put the index back to the tree from `index_tree` after a failed commit, which keeps
+    /// whatever was staged before the release
+    pub fn unstage(&self, root: &Utf8PathBuf, tree: Option<&str>) -> Result<(), Error> {
+        if let Some(tree) = tree {
+            git_out(root, &["read-tree", tree])?;
+        }
+
+        Ok(())
+    }
+
+    /// The message of the version commit, `None` when amending
+    pub fn commit_message(
+        &self,
+        new_version: &Option<Version>,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Option<String> {
+        if self.amend {
+            return None;
+        }
 
-            let mut msg = self.commit_msg(msg, new_versions);
+        let msg = self.message.as_deref().unwrap_or("Release %v");
 
-            msg = msg.replace(
+        Some(
+            self.commit_msg(msg, new_versions).replace(
                 "%v",
                 &new_version
                     .as_ref()
                     .map_or("independent packages".to_string(), |x| format!("{}", x)),
-            );
+            ),
+        )
+    }
 
-            args.push(msg);
+    /// The tags that would be created for these versions
+    pub fn tag_names(
+        &self,
+        new_version: &Option<Version>,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Vec<String> {
+        let mut tags = new_versions
+            .iter()
+            .filter(|(_, (pkg, _))| self.individual_tags_enabled(pkg.private))
+            .map(|(name, (_, version))| self.individual_tag_name(name, &version.to_string()))
+            .collect::<Vec<_>>();
+
+        if let Some(version) = new_version {
+            if self.global_tag_enabled() {
+                tags.push(format!("{}{}", self.tag_prefix(), version));
+            }
         }
 
-        let committed = git(root, &args.iter().map(|x| x.as_str()).collect::<Vec<_>>())?;
+        tags
+    }
 
-        if !committed.0.success() {
-            return Err(Error::NotCommitted(committed.1, committed.2));
-        }
+    fn global_tag_enabled(&self) -> bool {
+        !(self.no_git || self.no_git_tag || self.no_global_tag)
+    }
 
-        Ok(())
+    fn individual_tags_enabled(&self, is_private: bool) -> bool {
+        !(self.no_git
+            || self.no_git_tag
+            || self.no_individual_tags
+            || (is_private && !self.tag_private))
     }
 
     pub fn global_tag(
@@ -277,11 +627,11 @@ impl GitOpt {
         new_version: &Version,
         new_versions: &Map<String, (Pkg, Version)>,
     ) -> Result<Option<String>, Error> {
-        if self.no_git || self.no_git_tag || self.no_global_tag {
+        if !self.global_tag_enabled() {
             return Ok(None);
         }
 
-        let tag = format!("{}{}", &self.tag_prefix, new_version);
+        let tag = format!("{}{}", self.tag_prefix(), new_version);
         let mut msgs = Vec::with_capacity(self.tag_msg.capacity().max(1));
         for msg in &self.tag_msg {
             let mut s = String::new();
@@ -321,22 +671,12 @@ impl GitOpt {
         pkg_name: &str,
         is_private: bool,
         new_version: &str,
-        config: &WorkspaceConfig,
     ) -> Result<Option<String>, Error> {
-        if self.no_git
-            || self.no_git_tag
-            || self.no_individual_tags
-            || config.no_individual_tags.unwrap_or_default()
-            || (is_private && !self.tag_private)
-        {
+        if !self.individual_tags_enabled(is_private) {
             return Ok(None);
         }
 
-        let tag = format!(
-            "{}{}",
-            self.individual_tag_prefix.replace("%n", pkg_name),
-            new_version
-        );
+        let tag = self.individual_tag_name(pkg_name, new_version);
         let msg = self.individual_tag_msg.as_ref().map_or(tag.clone(), |msg| {
             msg.replace("%n", pkg_name).replace("%v", new_version)
         });
@@ -346,6 +686,14 @@ impl GitOpt {
         Ok(Some(tag))
     }
 
+    pub fn individual_tag_name(&self, pkg_name: &str, version: &str) -> String {
+        format!(
+            "{}{}",
+            self.individual_tag_prefix().replace("%n", pkg_name),
+            version
+        )
+    }
+
     pub fn push(
         &self,
         root: &Utf8PathBuf,
@@ -370,22 +718,66 @@ impl GitOpt {
 
         info!("git", "pushing");
 
-        let mut args = vec!["push", "--no-follow-tags", &self.git_remote];
-        args.extend(rest);
+        let mut args = vec!["push", "--no-follow-tags", "--atomic", self.git_remote()];
+        args.extend(&rest);
+
+        let mut pushed = git(root, &args)?;
+
+        if !pushed.0.success() && pushed.2.contains("does not support --atomic") {
+            info!(
+                "git",
+                "the remote does not support atomic pushes, pushing without"
+            );
+            args.retain(|x| *x != "--atomic");
+            pushed = git(root, &args)?;
+        }
+
+        let mut refs = vec![];
+        if let Some(branch) = &branch {
+            refs.push(format!("refs/heads/{}", branch));
+        }
+        refs.extend(tags.iter().map(|x| format!("refs/tags/{}", x)));
 
-        let pushed = git(root, &args)?;
+        let (landed, missing) = self.remote_refs(root, &refs)?;
 
         if !pushed.0.success() {
-            return Err(Error::NotPushed(pushed.1, pushed.2));
+            return Err(Error::PushFailed {
+                landed,
+                missing,
+                err: pushed.2,
+            });
+        }
+
+        if !missing.is_empty() {
+            return Err(Error::RefsNotOnRemote(missing));
         }
 
         Ok(())
     }
 
+    /// Split the refs into the ones on the remote pointing to the same objects as locally,
+    /// and the ones that are not
+    fn remote_refs(
+        &self,
+        root: &Utf8PathBuf,
+        refs: &[String],
+    ) -> Result<(Vec<String>, Vec<String>), Error> {
+        // Listed by name, so a ref missing locally can't shift the others
+        let mut args = vec!["for-each-ref", "--format=%(objectname)%09%(refname)"];
+        args.extend(refs.iter().map(|x| x.as_str()));
+        let local = git_out(root, &args)?;
+
+        let mut args = vec!["ls-remote", self.git_remote()];
+        args.extend(refs.iter().map(|x| x.as_str()));
+        let remote = git_out(root, &args)?;
+
+        Ok(split_landed(refs, &local, &remote))
+    }
+
     fn tag(&self, root: &Utf8PathBuf, tag: &str, msgs: &[String]) -> Result<(), Error> {
         let (_, tags, _) = git(root, &["tag"])?;
         if let None = tags.split("\n").find(|existing_tag| &tag == existing_tag) {
-            let mut args = vec!["tag", tag, "-a"];
+            let mut args = vec!["tag", tag, if self.sign_git_tag { "-s" } else { "-a" }];
             for msg in msgs {
                 args.extend(&["-m", &msg]);
             }
@@ -394,6 +786,9 @@ impl GitOpt {
             let tagged = git(root, &args)?;
 
             if !tagged.0.success() {
+                if self.sign_git_tag && signing_failed(&tagged.2) {
+                    return Err(Error::TagNotSigned(tag.to_string(), tagged.2));
+                }
                 return Err(Error::NotTagged(tag.to_string(), tagged.1, tagged.2));
             }
         } else {
@@ -407,13 +802,348 @@ impl GitOpt {
 
     fn commit_msg(&self, msg: &str, new_versions: &Map<String, (Pkg, Version)>) -> String {
         format!(
-            "{}\n\n{}\n\nGenerated by cargo-workspaces",
+            "{}\n\n{}\n\n{}",
             msg,
             new_versions
                 .iter()
                 .map(|x| format!("{}@{}", x.0, x.1 .1))
                 .collect::<Vec<_>>()
-                .join("\n")
+                .join("\n"),
+            TRAILER
         )
     }
+
+    /// Reset the last release commit and delete its tags, refusing to if the commit changed
+    /// files other than the release files (relative to the root) or is on a remote branch
+    pub fn undo<F>(&self, root: &Utf8PathBuf, is_release_file: F, remote: bool) -> Result<(), Error>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let msg = git_out(root, &["log", "-1", "--format=%B"])?;
+
+        if !msg.lines().any(|x| x == TRAILER) {
+            return Err(Error::NotReleaseCommit);
+        }
+
+        let prefix = git_out(root, &["rev-parse", "--show-prefix"])?;
+        let files = git_out(
+            root,
+            &["diff-tree", "--no-commit-id", "--name-only", "-r", "HEAD"],
+        )?;
+
+        let unrelated = files
+            .lines()
+            .filter(|file| {
+                !file
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(&is_release_file)
+            })
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        if !unrelated.is_empty() {
+            return Err(Error::UnrelatedChanges(unrelated));
+        }
+
+        let branches = git_out(root, &["branch", "-r", "--contains", "HEAD"])?;
+
+        // Resetting a pushed commit would rewrite the history of the remote branch
+        if !branches.is_empty() {
+            return Err(Error::ReleasePushed(
+                branches.lines().map(|x| x.trim().to_string()).collect(),
+            ));
+        }
+
+        let tags = git_out(root, &["tag", "--points-at", "HEAD"])?;
+        let tags = tags.lines().collect::<Vec<_>>();
+
+        info!("git", "resetting the release commit");
+
+        let reset = git(root, &["reset", "--keep", "HEAD~1"])?;
+
+        if !reset.0.success() {
+            return Err(Error::NotReset(reset.1, reset.2));
+        }
+
+        for tag in &tags {
+            let deleted = git(root, &["tag", "-d", tag])?;
+
+            if !deleted.0.success() {
+                return Err(Error::NotTagged(tag.to_string(), deleted.1, deleted.2));
+            }
+
+            info!("git", format!("deleted tag {}", tag));
+        }
+
+        if remote && !tags.is_empty() {
+            let (_, pushed, _) = git(root, &["ls-remote", "--tags", self.git_remote()])?;
+            let refs = tags
+                .iter()
+                .map(|x| format!("refs/tags/{}", x))
+                .filter(|x| {
+                    pushed
+                        .lines()
+                        .any(|line| line.ends_with(&format!("\t{}", x)))
+                })
+                .collect::<Vec<_>>();
+
+            if !refs.is_empty() {
+                info!("git", "deleting the pushed tags");
+
+                let mut args = vec!["push", self.git_remote(), "--delete"];
+                args.extend(refs.iter().map(|x| x.as_str()));
+
+                let deleted = git(root, &args)?;
+
+                if !deleted.0.success() {
+                    return Err(Error::NotPushed(deleted.1, deleted.2));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Output of a git command that is expected to succeed
+fn git_out(root: &Utf8PathBuf, args: &[&str]) -> Result<String, Error> {
+    let (status, out, err) = git(root, args)?;
+
+    if !status.success() {
+        return Err(Error::GitFailed {
+            args: args.join(" "),
+            err,
+        });
+    }
+
+    Ok(out)
+}
+
+/// Refuse uncommitted changes, which would be swept into the release commit, and untracked
+/// files inside the member crates, which would be left out of it
+fn check_clean(metadata: &Metadata) -> Result<(), Error> {
+    let root = &metadata.workspace_root;
+
+    // Without a commit to compare with, the tree can't be told clean, so this fails
+    let out = git_out(root, &["diff", "--name-only", "HEAD"])?;
+
+    if !out.is_empty() {
+        return Err(Error::UncommittedChanges(
+            out.lines().map(|x| x.to_string()).collect(),
+        ));
+    }
+
+    let members = if metadata
+        .packages
+        .iter()
+        .any(|p| p.manifest_path.parent() == Some(root.as_path()))
+    {
+        vec![".".to_string()]
+    } else {
+        member_paths(metadata)
+    };
+
+    let mut args = vec!["ls-files", "--others", "--exclude-standard", "--"];
+    args.extend(members.iter().map(|x| x.as_str()));
+
+    let out = git_out(root, &args)?;
+
+    if !out.is_empty() {
+        return Err(Error::UntrackedFiles(
+            out.lines().map(|x| x.to_string()).collect(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Split the refs by whether `ls-remote` lists them with the same objects as the local
+/// `for-each-ref`, both listing a ref per line as the object and name separated by a tab
+fn split_landed(refs: &[String], local: &str, remote: &str) -> (Vec<String>, Vec<String>) {
+    let objects = |out: &str| -> Map<String, String> {
+        out.lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(sha, name)| (name.to_string(), sha.to_string()))
+            .collect()
+    };
+
+    let (local, remote) = (objects(local), objects(remote));
+
+    let (landed, missing) = refs.iter().partition::<Vec<_>, _>(|name| {
+        local
+            .get(name.as_str())
+            .is_some_and(|sha| remote.get(name.as_str()) == Some(sha))
+    });
+
+    let names = |refs: Vec<&String>| refs.into_iter().cloned().collect();
+
+    (names(landed), names(missing))
+}
+
+/// Messages git prints when the GPG or SSH signer couldn't be run or failed
+const SIGNING_ERRORS: [&str; 7] = [
+    "gpg failed to sign the data",
+    "unable to sign the tag",
+    "unsupported value for gpg.format",
+    "invalid value for 'gpg.format'",
+    "ssh-keygen",
+    "couldn't load public key",
+    "either user.signingkey or gpg.ssh.defaultkeycommand",
+];
+
+/// Whether git failed because the GPG or SSH signing failed, rather than for another reason
+fn signing_failed(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+
+    if SIGNING_ERRORS.iter().any(|x| stderr.contains(x)) {
+        return true;
+    }
+
+    // A failing signer only leaves its own output, which may be empty, before this
+    let lines = stderr.lines().map(str::trim).collect::<Vec<_>>();
+
+    lines.windows(2).any(|x| {
+        x[1] == "fatal: failed to write commit object"
+            && (x[0] == "error:" || x[0].contains("gpg") || x[0].contains("ssh"))
+    })
+}
+
+/// Whether the two options can't be used together
+fn conflicting(a: &str, b: &str) -> bool {
+    CONFLICTS.iter().any(|(name, conflicts)| {
+        (*name == a && conflicts.contains(&b)) || (*name == b && conflicts.contains(&a))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::read_config;
+    use serde_json::json;
+
+    fn configure(args: &[&str], config: serde_json::Value) -> Result<GitOpt, Error> {
+        let mut git = GitOpt::parse_from([&["ws"], args].concat());
+        let config: WorkspaceConfig = read_config(&json!({ "workspaces": config })).unwrap();
+        git.configure(&config).map(|_| git)
+    }
+
+    #[test]
+    fn test_configure() {
+        let git = configure(
+            &[],
+            json!({ "tag_prefix": "release-", "no_global_tag": true }),
+        )
+        .unwrap();
+        assert_eq!(git.tag_prefix(), "release-");
+        assert!(git.no_global_tag);
+        assert_eq!(git.git_remote(), "origin");
+    }
+
+    #[test]
+    fn test_configure_cli_overrides() {
+        let git = configure(
+            &["--tag-prefix", "v", "--git-remote", "upstream"],
+            json!({ "tag_prefix": "release-" }),
+        )
+        .unwrap();
+        assert_eq!(git.tag_prefix(), "v");
+        assert_eq!(git.git_remote(), "upstream");
+
+        let git = configure(&["--amend"], json!({ "no_git_commit": true })).unwrap();
+        assert!(git.amend);
+        assert!(!git.no_git_commit);
+    }
+
+    #[test]
+    fn test_signing_failed() {
+        // gpg.program failing
+        assert!(signing_failed(
+            "error: gpg failed to sign the data\nfatal: failed to write commit object"
+        ));
+        assert!(signing_failed(
+            "error: gpg failed to sign the data\nerror: unable to sign the tag"
+        ));
+        // gpg.format with a typo
+        assert!(signing_failed(
+            "error: invalid value for 'gpg.format': 'foo'\nfatal: unable to parse 'gpg.format' from command-line config"
+        ));
+        // user.signingkey pointing to a missing ssh key
+        assert!(signing_failed(
+            "error: Couldn't load public key /tmp/missing.pub: No such file or directory?\n\nfatal: failed to write commit object"
+        ));
+        assert!(signing_failed(
+            "fatal: either user.signingkey or gpg.ssh.defaultKeyCommand needs to be configured"
+        ));
+        // gpg.ssh.program missing or failing without output
+        assert!(signing_failed(
+            "fatal: cannot run /usr/bin/ssh-keygen: No such file or directory\nerror: \nerror: unable to sign the tag"
+        ));
+        assert!(signing_failed(
+            "error:\nfatal: failed to write commit object"
+        ));
+
+        assert!(!signing_failed("fatal: tag 'v1.0.0' already exists"));
+        assert!(!signing_failed("please sign off your commits"));
+        assert!(!signing_failed(
+            "error: insufficient permission for adding an object to repository database .git/objects\nfatal: failed to write commit object"
+        ));
+    }
+
+    #[test]
+    fn test_split_landed() {
+        let refs = ["refs/heads/main", "refs/tags/v0.2.0", "refs/tags/foo@0.2.0"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        let local = "aaa\trefs/heads/main\nbbb\trefs/tags/v0.2.0\nccc\trefs/tags/foo@0.2.0";
+        let remote = "aaa\trefs/heads/main\nddd\trefs/tags/foo@0.2.0";
+
+        assert_eq!(
+            split_landed(&refs, local, remote),
+            (
+                vec!["refs/heads/main".to_string()],
+                vec![
+                    "refs/tags/v0.2.0".to_string(),
+                    "refs/tags/foo@0.2.0".to_string()
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_split_landed_missing_local() {
+        let refs = ["refs/heads/main", "refs/tags/v0.2.0", "refs/tags/foo@0.2.0"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        // The tag v0.2.0 is missing locally, and foo@0.2.0 points elsewhere on the remote
+        let local = "aaa\trefs/heads/main\nccc\trefs/tags/foo@0.2.0";
+        let remote = "aaa\trefs/heads/main\nbbb\trefs/tags/v0.2.0\nddd\trefs/tags/foo@0.2.0";
+
+        assert_eq!(
+            split_landed(&refs, local, remote),
+            (
+                vec!["refs/heads/main".to_string()],
+                vec![
+                    "refs/tags/v0.2.0".to_string(),
+                    "refs/tags/foo@0.2.0".to_string()
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_configure_conflicts() {
+        assert!(matches!(
+            configure(
+                &[],
+                json!({ "no_git_commit": true, "message": "Release %v" })
+            ),
+            Err(Error::ConflictingConfig(..))
+        ));
+        assert!(matches!(
+            configure(&[], json!({ "individual_tag_prefix": "v" })),
+            Err(Error::BadConfigValue { .. })
+        ));
+    }
 }
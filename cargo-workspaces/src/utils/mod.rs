@@ -1,26 +1,51 @@
+mod calver;
 mod cargo;
 mod changable;
+mod changelog;
+mod changeset;
 mod config;
 mod dag;
+mod date;
 mod error;
 mod git;
 mod listable;
 mod pkg;
+mod plan;
+mod pre;
+mod sync;
+mod transaction;
 mod version;
+mod version_file;
 
+pub use calver::{CalverFormat, DEFAULT_CALVER_FORMAT};
 pub use cargo::{
-    cargo, cargo_config_get, change_versions, check_index, is_published, is_unversioned,
-    rename_packages, ManifestDiscriminant,
+    cargo, cargo_config_get, change_lock_versions, change_versions, check_index, is_published,
+    is_unversioned, rename_packages, ManifestDiscriminant,
 };
 pub use changable::{ChangeData, ChangeOpt};
-pub use config::{read_config, PackageConfig, WorkspaceConfig};
+pub use changelog::{
+    changelog_path, commits, diffstat, member_paths, nested_paths, slash_path, write_changelogs,
+    ChangelogFormat,
+};
+pub use changeset::{changeset_path, parse_level, Changeset, Changesets, CHANGESET_DIR};
+pub use config::{
+    read_config, BranchPolicySpec, DependentBump, GroupPolicy, PackageConfig, ReleaseLevel,
+    WorkspaceConfig,
+};
 pub use dag::dag;
+pub use date::{today, Date};
 pub(crate) use error::{debug, info};
-pub use error::{get_debug, set_debug, Error};
+pub use error::{get_debug, interactive, set_debug, set_no_interactive, Error};
+pub(crate) use git::TRAILER;
 pub use git::{git, GitOpt};
 pub use listable::{ListOpt, Listable};
 pub use pkg::{get_group_packages, GroupName, Pkg, WorkspaceGroups};
-pub use version::VersionOpt;
+pub use plan::Plan;
+pub use pre::{stable, validate_pre_tag, PreMode, PreState};
+pub use sync::sync_version;
+pub use transaction::Transaction;
+pub use version::{Bump, Release, VersionOpt};
+pub use version_file::VersionFileSpec;
 
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -1,5 +1,6 @@
 use crate::utils::{
-    read_config, Error, ListOpt, Listable, PackageConfig, Result, WorkspaceConfig, INTERNAL_ERR,
+    read_config, Error, GroupPolicy, ListOpt, Listable, PackageConfig, Result, WorkspaceConfig,
+    INTERNAL_ERR,
 };
 
 use camino::Utf8PathBuf;
@@ -237,6 +238,11 @@ pub fn get_group_packages(
                         name: group.name.clone(),
                     });
                 }
+                if group.policy == Some(GroupPolicy::Independent) && group.version.is_some() {
+                    return Err(Error::IndependentGroupVersion {
+                        name: group.name.clone(),
+                    });
+                }
                 acc.insert(group_name, (group.version.clone(), vec![]));
             }
             acc
@@ -342,6 +348,15 @@ pub fn get_group_packages(
                 };
             };
 
+            if pkg.config.independent.unwrap_or(false)
+                && workspace_config.policy(&group_name) == GroupPolicy::Fixed
+            {
+                return Err(Error::IndependentInFixedGroup {
+                    name: pkg.name,
+                    group: group_name.to_string(),
+                });
+            }
+
             named_groups
                 .get_mut(&group_name)
                 .expect(INTERNAL_ERR)
@@ -0,0 +1,246 @@
+use crate::utils::{
+    get_group_packages, git, Changesets, Error, GroupName, Pkg, PreState, Release, Result,
+    WorkspaceConfig, CHANGESET_DIR, INTERNAL_ERR,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_metadata::Metadata;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use std::{collections::BTreeMap as Map, fs};
+
+/// A release computed by `version --plan-out`, to be applied by `version --plan-in`
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    /// Commit the plan was computed at
+    pub head: Option<String>,
+    /// Tag the changes were detected since
+    pub since: Option<String>,
+    /// Git object hashes of the files the release reads, relative to the workspace root
+    pub files: Map<String, String>,
+    pub version: Option<String>,
+    pub crates: Map<String, PlanCrate>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlanCrate {
+    pub group: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Plan {
+    pub fn new(
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        since: Option<String>,
+        new_version: &Option<Version>,
+        new_versions: &Map<String, (Pkg, Version)>,
+        tags: Vec<String>,
+    ) -> Result<Self> {
+        let groups = get_group_packages(metadata, config, true)?
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        let crates = new_versions
+            .iter()
+            .map(|(name, (pkg, version))| {
+                let group = groups
+                    .iter()
+                    .find(|(_, p)| p.id == pkg.id)
+                    .map_or(GroupName::Default, |((group, _), _)| group.clone());
+
+                (
+                    name.clone(),
+                    PlanCrate {
+                        group: group.to_string(),
+                        from: pkg.version.to_string(),
+                        to: version.to_string(),
+                    },
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            head: head(&metadata.workspace_root)?,
+            since,
+            files: file_hashes(metadata, config)?,
+            version: new_version.as_ref().map(|x| x.to_string()),
+            crates,
+            tags,
+        })
+    }
+
+    pub fn read(path: &Utf8Path) -> Result<Self> {
+        serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| Error::BadPlan(format!("{}: {}", path, err)))
+    }
+
+    pub fn write(&self, path: &Utf8Path) -> Result {
+        fs::write(path, format!("{}\n", serde_json::to_string_pretty(self)?))?;
+
+        Ok(())
+    }
+
+    /// Refuse plans computed against a different HEAD or different inputs
+    pub fn verify(&self, metadata: &Metadata, config: &WorkspaceConfig) -> Result {
+        let head = head(&metadata.workspace_root)?;
+
+        if self.head != head {
+            return Err(Error::StalePlan(format!(
+                "it was computed at {} but HEAD is at {}",
+                self.head.as_deref().unwrap_or("<none>"),
+                head.as_deref().unwrap_or("<none>")
+            )));
+        }
+
+        let files = file_hashes(metadata, config)?;
+
+        for (path, hash) in &self.files {
+            if files.get(path) != Some(hash) {
+                return Err(Error::StalePlan(format!("{} has changed", path)));
+            }
+        }
+
+        if let Some(path) = files.keys().find(|x| !self.files.contains_key(*x)) {
+            return Err(Error::StalePlan(format!("{} was added", path)));
+        }
+
+        Ok(())
+    }
+
+    /// The planned release, resolved against the workspace
+    pub fn release(self, metadata: &Metadata, config: &WorkspaceConfig) -> Result<Release> {
+        let bad = |err: semver::SemVerError| Error::BadPlan(err.to_string());
+
+        let new_version = match &self.version {
+            Some(version) => Some(Version::parse(version).map_err(bad)?),
+            None => None,
+        };
+
+        let pkgs = get_group_packages(metadata, config, true)?
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut new_versions = Map::new();
+
+        for (name, planned) in &self.crates {
+            let (_, pkg) = pkgs
+                .iter()
+                .find(|(_, p)| &p.name == name)
+                .ok_or_else(|| Error::PackageNotFound { id: name.clone() })?;
+
+            let version = Version::parse(&planned.to).map_err(bad)?;
+
+            new_versions.insert(name.clone(), (pkg.clone(), version));
+        }
+
+        Ok(Release {
+            since: self.since,
+            changesets: Changesets::read(&metadata.workspace_root)?,
+            pre: PreState::read(&metadata.workspace_root)?,
+            new_version,
+            new_versions,
+        })
+    }
+}
+
+fn head(root: &Utf8PathBuf) -> Result<Option<String>> {
+    let (status, out, _) = git(root, &["rev-parse", "HEAD"])?;
+
+    Ok(if status.success() { Some(out) } else { None })
+}
+
+/// Hashes of the manifests, lockfile, changesets, prerelease state, synced files and
+/// version files, which are all read when applying the plan
+fn file_hashes(metadata: &Metadata, config: &WorkspaceConfig) -> Result<Map<String, String>> {
+    let root = &metadata.workspace_root;
+
+    let mut files = metadata
+        .packages
+        .iter()
+        .map(|p| p.manifest_path.clone())
+        .chain(vec![root.join("Cargo.toml"), root.join("Cargo.lock")])
+        .collect::<Vec<_>>();
+
+    for (_, pkg) in get_group_packages(metadata, config, true)?.into_iter() {
+        let dir = pkg.manifest_path.parent().expect(INTERNAL_ERR);
+        files.extend(pkg.config.sync.iter().flatten().map(|x| dir.join(x)));
+    }
+
+    files.extend(config.version_files.iter().map(|x| root.join(&x.path)));
+
+    let changesets = root.join(CHANGESET_DIR);
+    if changesets.is_dir() {
+        files.extend(
+            fs::read_dir(&changesets)?
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok()),
+        );
+    }
+
+    let mut paths = files
+        .into_iter()
+        .filter(|x| x.is_file())
+        .filter_map(|x| x.strip_prefix(root).ok().map(|x| x.to_string()))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    let mut args = vec!["hash-object", "--"];
+    args.extend(paths.iter().map(|x| x.as_str()));
+
+    let (status, out, _) = git(root, &args)?;
+
+    if !status.success() {
+        return Err(Error::NotGit);
+    }
+
+    Ok(paths
+        .into_iter()
+        .zip(out.lines().map(|x| x.to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_round_trip() {
+        let plan = indoc! {r#"
+            {
+              "head": "0123456789abcdef",
+              "since": "v0.1.0",
+              "files": {
+                "Cargo.toml": "abc"
+              },
+              "version": "0.2.0",
+              "crates": {
+                "dep1": {
+                  "group": "default",
+                  "from": "0.1.0",
+                  "to": "0.2.0"
+                }
+              },
+              "tags": [
+                "v0.2.0"
+              ]
+            }"#
+        };
+
+        let parsed: Plan = serde_json::from_str(plan).unwrap();
+        assert_eq!(parsed.crates["dep1"].to, "0.2.0");
+        assert_eq!(serde_json::to_string_pretty(&parsed).unwrap(), plan);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let plan = r#"{"head": null, "since": null, "files": {}, "version": null, "crates": {}, "tags": [], "extra": 1}"#;
+        assert!(serde_json::from_str::<Plan>(plan).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::utils::{Error, Result, Transaction, CHANGESET_DIR};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use semver::{Identifier, Version};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+const PRE_FILE: &str = "pre.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreMode {
+    /// Versions are released as prereleases of the tag
+    Pre,
+    /// The next release graduates the prereleases to stable versions
+    Exit,
+}
+
+/// Persistent prerelease mode, stored in `.changeset/pre.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreState {
+    pub mode: PreMode,
+    pub tag: String,
+}
+
+impl PreState {
+    pub fn path(root: &Utf8Path) -> Utf8PathBuf {
+        root.join(CHANGESET_DIR).join(PRE_FILE)
+    }
+
+    pub fn read(root: &Utf8Path) -> Result<Option<Self>> {
+        let path = Self::path(root);
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&fs::read_to_string(&path)?)
+            .map(Some)
+            .map_err(|err| Error::BadPreState(format!("{}: {}", path, err)))
+    }
+
+    pub fn write(&self, root: &Utf8Path) -> Result {
+        fs::create_dir_all(root.join(CHANGESET_DIR))?;
+        fs::write(
+            Self::path(root),
+            format!("{}\n", serde_json::to_string_pretty(self)?),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn remove(root: &Utf8Path, transaction: &mut Transaction) -> Result {
+        transaction.remove(&Self::path(root))
+    }
+
+    /// Whether the version is a prerelease of this tag
+    pub fn is_tagged(&self, version: &Version) -> bool {
+        matches!(version.pre.first(), Some(Identifier::AlphaNumeric(id)) if *id == self.tag)
+    }
+
+    /// Turn the next stable version into the next prerelease of the tag, counting up from
+    /// the current version when it is already a prerelease of the same version
+    pub fn prerelease(&self, cur_version: &Version, next: &Version) -> Version {
+        let base = stable(next);
+
+        let n = match cur_version.pre.as_slice() {
+            [Identifier::AlphaNumeric(id), Identifier::Numeric(n)]
+                if *id == self.tag && stable(cur_version) == base =>
+            {
+                n + 1
+            }
+            _ => 0,
+        };
+
+        let mut version = base;
+        version.pre = vec![
+            Identifier::AlphaNumeric(self.tag.clone()),
+            Identifier::Numeric(n),
+        ];
+        version
+    }
+}
+
+/// The version without its prerelease and build metadata
+pub fn stable(version: &Version) -> Version {
+    let mut version = version.clone();
+    version.pre.clear();
+    version.build.clear();
+    version
+}
+
+pub fn validate_pre_tag(tag: &str) -> std::result::Result<(), String> {
+    if tag.is_empty()
+        || tag.bytes().all(|c| c.is_ascii_digit())
+        || !tag.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+    {
+        return Err("must be an alphanumeric prerelease identifier\n".to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn beta() -> PreState {
+        PreState {
+            mode: PreMode::Pre,
+            tag: "beta".to_string(),
+        }
+    }
+
+    fn pre(cur: &str, next: &str) -> String {
+        beta()
+            .prerelease(
+                &Version::parse(cur).unwrap(),
+                &Version::parse(next).unwrap(),
+            )
+            .to_string()
+    }
+
+    #[test]
+    fn test_prerelease_from_stable() {
+        assert_eq!(pre("1.0.0", "1.1.0"), "1.1.0-beta.0");
+    }
+
+    #[test]
+    fn test_prerelease_same_version() {
+        assert_eq!(pre("1.1.0-beta.0", "1.1.0"), "1.1.0-beta.1");
+        assert_eq!(pre("1.1.0-beta.7", "1.1.0"), "1.1.0-beta.8");
+    }
+
+    #[test]
+    fn test_prerelease_higher_version() {
+        assert_eq!(pre("1.1.0-beta.3", "2.0.0"), "2.0.0-beta.0");
+    }
+
+    #[test]
+    fn test_prerelease_other_tag() {
+        assert_eq!(pre("1.1.0-alpha.3", "1.1.0"), "1.1.0-beta.0");
+    }
+
+    #[test]
+    fn test_is_tagged() {
+        assert!(beta().is_tagged(&Version::parse("1.0.0-beta.1").unwrap()));
+        assert!(!beta().is_tagged(&Version::parse("1.0.0-alpha.1").unwrap()));
+        assert!(!beta().is_tagged(&Version::parse("1.0.0").unwrap()));
+    }
+
+    #[test]
+    fn test_validate_pre_tag() {
+        assert!(validate_pre_tag("beta").is_ok());
+        assert!(validate_pre_tag("rc-1").is_ok());
+        assert!(validate_pre_tag("1").is_err());
+        assert!(validate_pre_tag("beta.1").is_err());
+    }
+}
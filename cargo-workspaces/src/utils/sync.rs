@@ -0,0 +1,253 @@
+use crate::utils::{Error, Result, INTERNAL_ERR};
+
+use camino::Utf8Path;
+use semver::{Identifier, Version};
+use serde_json::Value;
+use toml_edit::{value, Document, Item};
+
+/// Rewrite the version of a binding manifest shipped with a crate, like the `package.json`
+/// built by wasm-pack or the `pyproject.toml` built by maturin
+pub fn sync_version(path: &Utf8Path, contents: &str, version: &Version) -> Result<String> {
+    let err = |msg: &str| Error::BadSyncFile {
+        path: path.to_string(),
+        msg: msg.to_string(),
+    };
+
+    match path.extension() {
+        Some("json") => {
+            let manifest =
+                serde_json::from_str::<Value>(contents).map_err(|e| err(&e.to_string()))?;
+
+            if !manifest.get("version").is_some_and(Value::is_string) {
+                return Err(err("no version field was found"));
+            }
+
+            // Only the value is replaced, to leave the formatting and key order of the file as is
+            let (start, end) = json_version_span(contents).expect(INTERNAL_ERR);
+
+            Ok(format!(
+                "{}{}{}",
+                &contents[..start],
+                version,
+                &contents[end..]
+            ))
+        }
+        Some("toml") => {
+            let mut manifest = contents
+                .parse::<Document>()
+                .map_err(|e| err(&e.to_string()))?;
+
+            let table = ["project", "tool.poetry"]
+                .iter()
+                .find(|table| {
+                    table
+                        .split('.')
+                        .try_fold(manifest.as_item(), |item, key| item.get(key))
+                        .and_then(|x| x.get("version"))
+                        .is_some_and(Item::is_str)
+                })
+                .ok_or_else(|| err("no version field was found"))?;
+
+            let field = &mut table
+                .split('.')
+                .fold(manifest.as_item_mut(), |item, key| &mut item[key])["version"];
+
+            let decor = field.as_value().map(|x| x.decor().clone());
+            *field = value(pep440(version).map_err(|msg| err(&msg))?);
+
+            if let (Some(decor), Item::Value(field)) = (decor, field) {
+                *field.decor_mut() = decor;
+            }
+
+            Ok(manifest.to_string())
+        }
+        _ => Err(err("expected a json or toml file")),
+    }
+}
+
+/// Byte range of the string value of the top level `version` key in a valid json document
+fn json_version_span(contents: &str) -> Option<(usize, usize)> {
+    let bytes = contents.as_bytes();
+    let (mut depth, mut key, mut is_value) = (0, None, false);
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i + 1;
+                i = start;
+                while bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+
+                if depth == 1 && is_value {
+                    if key == Some("version") {
+                        return Some((start, i));
+                    }
+                    is_value = false;
+                } else if depth == 1 {
+                    key = Some(&contents[start..i]);
+                }
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                is_value = false;
+            }
+            b'}' | b']' => depth -= 1,
+            b':' if depth == 1 => is_value = true,
+            b',' if depth == 1 => is_value = false,
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Python spells the `alpha`, `beta`, `rc` and `dev` prereleases as `1.0.0a1`, `1.0.0b1`,
+/// `1.0.0rc1` and `1.0.0.dev1`, and the build metadata as a local version
+fn pep440(version: &Version) -> std::result::Result<String, String> {
+    let base = format!("{}.{}.{}", version.major, version.minor, version.patch);
+
+    let pre = match version.pre.as_slice() {
+        [] => "".to_string(),
+        [Identifier::AlphaNumeric(id), Identifier::Numeric(n)] => match id.as_str() {
+            "alpha" | "a" => format!("a{}", n),
+            "beta" | "b" => format!("b{}", n),
+            "rc" => format!("rc{}", n),
+            "dev" => format!(".dev{}", n),
+            _ => return Err(no_pep440(version)),
+        },
+        _ => return Err(no_pep440(version)),
+    };
+
+    let local = match version.build.as_slice() {
+        [] => "".to_string(),
+        build => format!(
+            "+{}",
+            build
+                .iter()
+                .map(|x| x.to_string().replace('-', "."))
+                .collect::<Vec<_>>()
+                .join(".")
+        ),
+    };
+
+    Ok(format!("{}{}{}", base, pre, local))
+}
+
+fn no_pep440(version: &Version) -> String {
+    format!(
+        "version {} has no PEP 440 equivalent, only the alpha.N, beta.N, rc.N and dev.N prereleases can be synced",
+        version
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    fn sync(path: &str, contents: &str, version: &str) -> Result<String> {
+        sync_version(
+            Utf8Path::new(path),
+            contents,
+            &Version::parse(version).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_package_json() {
+        let contents = indoc! {r#"
+            {
+              "name": "foo",
+              "version": "0.1.0",
+              "files": [
+                "foo_bg.wasm"
+              ]
+            }
+        "#};
+
+        assert_eq!(
+            sync("pkg/package.json", contents, "0.2.0").unwrap(),
+            contents.replace("0.1.0", "0.2.0")
+        );
+    }
+
+    #[test]
+    fn test_package_json_formatting() {
+        let contents = indoc! {r#"
+            {
+                "name": "foo",
+                "dependencies": { "bar": "0.1.0", "version": "0.1.0" },
+                "scripts": ["\"version\"", "x"],
+                "version" : "0.1.0",
+                "description": "a"
+            }"#};
+
+        assert_eq!(
+            sync("package.json", contents, "0.2.0-canary.abc").unwrap(),
+            contents.replace(
+                r#""version" : "0.1.0""#,
+                r#""version" : "0.2.0-canary.abc""#
+            )
+        );
+    }
+
+    #[test]
+    fn test_pyproject() {
+        let contents = indoc! {r#"
+            [build-system]
+            requires = ["maturin>=1.0"]
+
+            [project]
+            name = "foo"
+            version = "0.1.0" # kept in sync
+            dependencies = ["bar==0.1.0"]
+        "#};
+
+        assert_eq!(
+            sync("pyproject.toml", contents, "0.2.0-beta.1").unwrap(),
+            contents.replace("\"0.1.0\" #", "\"0.2.0b1\" #")
+        );
+    }
+
+    #[test]
+    fn test_poetry() {
+        let contents = "[tool.poetry]\nname = \"foo\"\nversion = \"0.1.0\"\n";
+
+        assert_eq!(
+            sync("pyproject.toml", contents, "0.2.0").unwrap(),
+            "[tool.poetry]\nname = \"foo\"\nversion = \"0.2.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_no_version() {
+        assert!(sync("package.json", r#"{"name": "foo"}"#, "0.2.0").is_err());
+        assert!(sync(
+            "pyproject.toml",
+            "[project]\nname = \"foo\"\ndynamic = [\"version\"]\n",
+            "0.2.0"
+        )
+        .is_err());
+        assert!(sync("setup.py", "", "0.2.0").is_err());
+    }
+
+    #[test]
+    fn test_pep440() {
+        let pep440 = |v| pep440(&Version::parse(v).unwrap());
+
+        assert_eq!(pep440("1.0.0").unwrap(), "1.0.0");
+        assert_eq!(pep440("1.0.0-alpha.2").unwrap(), "1.0.0a2");
+        assert_eq!(pep440("1.0.0-rc.0").unwrap(), "1.0.0rc0");
+        assert_eq!(pep440("1.0.0-dev.1").unwrap(), "1.0.0.dev1");
+        assert_eq!(
+            pep440("1.0.1-dev.3+gabc123.dirty").unwrap(),
+            "1.0.1.dev3+gabc123.dirty"
+        );
+        assert!(pep440("1.0.1-canary.abc123").is_err());
+        assert!(pep440("1.0.0-preview.1").is_err());
+    }
+}
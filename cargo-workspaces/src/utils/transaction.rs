@@ -0,0 +1,158 @@
+use crate::utils::{Error, Result};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+
+const STAGED_SUFFIX: &str = "cw-staged";
+
+/// Files changed while releasing, which are restored if the release fails before it's committed
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Contents of the files before they were first changed, `None` if they didn't exist
+    originals: Vec<(Utf8PathBuf, Option<String>)>,
+}
+
+impl Transaction {
+    /// Remember the file as it is, before something else changes it
+    pub fn track(&mut self, path: &Utf8Path) -> Result {
+        if self.originals.iter().any(|(x, _)| x == path) {
+            return Ok(());
+        }
+
+        let original = if path.is_file() {
+            Some(fs::read_to_string(path)?)
+        } else {
+            None
+        };
+
+        self.originals.push((path.to_path_buf(), original));
+
+        Ok(())
+    }
+
+    /// Write the files together, staging all of them next to their targets
+    /// before moving them into place, so a failed write changes none of them
+    pub fn write_all<'a, I>(&mut self, files: I) -> Result
+    where
+        I: IntoIterator<Item = (&'a Utf8Path, &'a str)>,
+    {
+        let mut staged = vec![];
+
+        for (path, contents) in files {
+            let staged_path = Utf8PathBuf::from(format!("{}.{}", path, STAGED_SUFFIX));
+
+            if let Err(err) = fs::write(&staged_path, contents) {
+                for (_, x) in &staged {
+                    let _ = fs::remove_file(x);
+                }
+
+                return Err(err.into());
+            }
+
+            staged.push((path, staged_path));
+        }
+
+        for (path, _) in &staged {
+            self.track(path)?;
+        }
+
+        for (path, staged_path) in staged {
+            fs::rename(staged_path, path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write(&mut self, path: &Utf8Path, contents: &str) -> Result {
+        self.write_all(Some((path, contents)))
+    }
+
+    pub fn remove(&mut self, path: &Utf8Path) -> Result {
+        self.track(path)?;
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    /// Restore every file to how it was before, carrying on past failures
+    /// and returning the first of them
+    pub fn rollback(self) -> Result {
+        let mut result = Ok(());
+
+        for (path, original) in self.originals.into_iter().rev() {
+            let restored = match original {
+                Some(contents) => fs::write(&path, contents),
+                None if path.is_file() => fs::remove_file(&path),
+                None => Ok(()),
+            };
+
+            if let Err(err) = restored {
+                if result.is_ok() {
+                    result = Err(Error::Rollback {
+                        path: path.to_string(),
+                        err,
+                    });
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dir(name: &str) -> Utf8PathBuf {
+        let dir = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+            .unwrap()
+            .join(format!("cargo-workspaces-transaction-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rollback() {
+        let dir = dir("rollback");
+        let (a, b, c) = (dir.join("a"), dir.join("b"), dir.join("c"));
+        fs::write(&a, "a").unwrap();
+        fs::write(&b, "b").unwrap();
+
+        let mut transaction = Transaction::default();
+        transaction
+            .write_all(vec![(a.as_path(), "a2"), (c.as_path(), "c2")])
+            .unwrap();
+        transaction.write(&a, "a3").unwrap();
+        transaction.remove(&b).unwrap();
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a3");
+        assert!(!b.exists());
+        assert!(!Utf8PathBuf::from(format!("{}.{}", a, STAGED_SUFFIX)).exists());
+
+        transaction.rollback().unwrap();
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "b");
+        assert!(!c.exists());
+    }
+
+    #[test]
+    fn test_failed_write() {
+        let dir = dir("failed");
+        let a = dir.join("a");
+        fs::write(&a, "a").unwrap();
+
+        let mut transaction = Transaction::default();
+        let missing = dir.join("missing/b");
+        assert!(transaction
+            .write_all(vec![(a.as_path(), "a2"), (missing.as_path(), "b2")])
+            .is_err());
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "a");
+        assert!(transaction.originals.is_empty());
+    }
+}
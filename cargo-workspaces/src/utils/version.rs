@@ -1,24 +1,32 @@
 use crate::utils::{
-    cargo, change_versions, info, is_unversioned, ChangeData, ChangeOpt, Error, GitOpt, GroupName,
-    ManifestDiscriminant, Pkg, Result, WorkspaceConfig, INTERNAL_ERR,
+    cargo, change_lock_versions, change_versions, changelog_path, commits, diffstat,
+    get_group_packages, info, interactive, is_unversioned, member_paths, nested_paths, slash_path,
+    stable, sync_version, today, write_changelogs, ChangeData, ChangeOpt, Changesets,
+    DependentBump, Error, GitOpt, GroupName, GroupPolicy, ManifestDiscriminant, Pkg, PreMode,
+    PreState, ReleaseLevel, Result, Transaction, WorkspaceConfig, CHANGESET_DIR, INTERNAL_ERR,
 };
 
+use camino::{Utf8Path, Utf8PathBuf};
 use cargo_metadata::Metadata;
 use clap::{ArgEnum, Parser};
-use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect, Select};
+use globset::Glob;
 use oclif::{
     console::style,
     term::{TERM_ERR, TERM_OUT},
+    CliError,
 };
 use semver::{Identifier, Version, VersionReq};
+use similar::TextDiff;
 
 use std::{
     collections::{BTreeMap as Map, HashMap, HashSet},
-    fs,
+    fmt, fs,
     process::exit,
+    str::FromStr,
 };
 
-#[derive(Debug, Clone, ArgEnum)]
+#[derive(Debug, Clone, PartialEq, Eq, ArgEnum)]
 pub enum Bump {
     Major,
     Minor,
@@ -28,6 +36,8 @@ pub enum Bump {
     Prepatch,
     Prerelease,
     Custom,
+    Graduate,
+    Promote,
 }
 
 impl Bump {
@@ -41,10 +51,103 @@ impl Bump {
             Bump::Prepatch => 3,
             Bump::Prerelease => 6,
             Bump::Custom => 7,
+            Bump::Graduate => 8,
+            Bump::Promote => 9,
         }
     }
 }
 
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => f.write_str(value.get_name()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A bump level or an exact version for a crate or group
+#[derive(Debug, Clone, PartialEq)]
+pub enum BumpSpec {
+    Bump(Bump),
+    Version(Version),
+}
+
+impl FromStr for BumpSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.strip_prefix('=').unwrap_or(s);
+
+        if let Ok(version) = Version::parse(s) {
+            return Ok(BumpSpec::Version(version));
+        }
+
+        match Bump::from_str(s, true) {
+            Ok(Bump::Custom) | Err(_) => Err(format!(
+                "invalid bump `{}`, expected a version or one of major, minor, patch, premajor, preminor, prepatch, prerelease, graduate, promote",
+                s
+            )),
+            Ok(bump) => Ok(BumpSpec::Bump(bump)),
+        }
+    }
+}
+
+fn parse_bump_crate(value: &str) -> std::result::Result<(String, BumpSpec), String> {
+    let (name, spec) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `crate=bump`, found `{}`", value))?;
+
+    Ok((name.to_string(), spec.parse()?))
+}
+
+fn parse_bump_group(value: &str) -> std::result::Result<(GroupName, BumpSpec), String> {
+    let (name, spec) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected `group=bump`, found `{}`", value))?;
+
+    Ok((name.parse()?, spec.parse()?))
+}
+
+fn parse_promote(value: &str) -> std::result::Result<(String, String), String> {
+    match value.split_once("->") {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((from.to_string(), to.to_string()))
+        }
+        _ => Err(format!("expected `from->to`, found `{}`", value)),
+    }
+}
+
+/// Default order of the prerelease channels for `--promote`
+const DEFAULT_CHANNELS: [&str; 3] = ["alpha", "beta", "rc"];
+const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// What besides the command line decides the bumps
+struct BumpHints<'a> {
+    /// Bumps recorded in changesets
+    changesets: HashMap<String, Bump>,
+    /// Bumps cascading to dependents from the crates bumped in the previous round
+    dependents: HashMap<String, Bump>,
+    pre: Option<&'a PreState>,
+    /// Prerelease channels in the order crates are promoted along
+    channels: Vec<String>,
+    /// Workspace and baseline the commits shown in the prompts are read from
+    metadata: &'a Metadata,
+    since: Option<&'a str>,
+    /// Current branch and the largest release its policy allows
+    branch_policy: Option<(String, ReleaseLevel)>,
+}
+
+/// New versions picked for a release, along with the changes they were picked from
+#[derive(Debug)]
+pub struct Release {
+    pub since: Option<String>,
+    pub changesets: Changesets,
+    pub pre: Option<PreState>,
+    pub new_version: Option<Version>,
+    pub new_versions: Map<String, (Pkg, Version)>,
+}
+
 #[derive(Debug, Parser)]
 #[clap(next_help_heading = "VERSION OPTIONS")]
 pub struct VersionOpt {
@@ -61,6 +164,40 @@ pub struct VersionOpt {
     #[clap(long, value_name = "identifier", forbid_empty_values(true))]
     pub pre_id: Option<String>,
 
+    /// Bump a crate by the given semver keyword or to the given version (e.g. `foo=minor`, `foo=1.2.3`)
+    #[clap(
+        long,
+        value_name = "crate=bump",
+        multiple_occurrences = true,
+        parse(try_from_str = parse_bump_crate)
+    )]
+    pub bump_crate: Vec<(String, BumpSpec)>,
+
+    /// Bump a group by the given semver keyword or to the given version (e.g. `core=major`)
+    #[clap(
+        long,
+        value_name = "group=bump",
+        multiple_occurrences = true,
+        parse(try_from_str = parse_bump_group)
+    )]
+    pub bump_group: Vec<(GroupName, BumpSpec)>,
+
+    /// Graduate the prereleases of crates matched by glob to their stable version,
+    /// even when there are no changes (all crates when no glob is given)
+    #[clap(
+        long,
+        value_name = "pattern",
+        min_values = 0,
+        use_value_delimiter = true,
+        parse(try_from_str = Glob::new)
+    )]
+    pub graduate: Option<Vec<Glob>>,
+
+    /// Move the prereleases on a channel to the first prerelease of a later channel,
+    /// even when there are no changes (e.g. `alpha->beta`)
+    #[clap(long, value_name = "from->to", parse(try_from_str = parse_promote))]
+    pub promote: Option<(String, String)>,
+
     #[clap(flatten)]
     pub change: ChangeOpt,
 
@@ -79,6 +216,10 @@ pub struct VersionOpt {
     #[clap(short, long)]
     pub yes: bool,
 
+    /// Pick the crates to release from a list, with the changed crates already picked
+    #[clap(long)]
+    pub select: bool,
+
     /// Comma separated list of crate groups to version
     #[clap(
         long,
@@ -88,27 +229,92 @@ pub struct VersionOpt {
     )]
     pub groups: Vec<GroupName>,
 
-    /// Do not use a pager for previewing package groups in interactive mode
+    /// Do not use a pager for previewing package groups and commits in interactive mode
     #[clap(long)]
     pub no_pager: bool,
 }
 
 impl VersionOpt {
+    /// Fill in the options missing from the command line with the workspace config
+    pub fn configure(&mut self, config: &WorkspaceConfig) -> Result {
+        self.git.configure(config)?;
+        self.change.configure(config);
+
+        if self.pre_id.is_none() {
+            self.pre_id = config.pre_id.clone();
+        }
+
+        if self.groups.is_empty() {
+            self.groups = config
+                .version_groups
+                .iter()
+                .flatten()
+                .map(|x| {
+                    x.parse().map_err(|msg| Error::BadConfigValue {
+                        key: "groups".to_string(),
+                        msg,
+                    })
+                })
+                .collect::<Result<_>>()?;
+        }
+
+        self.all |= config.all.unwrap_or_default();
+        self.exact |= config.exact.unwrap_or_default();
+        self.yes |= config.yes.unwrap_or_default();
+        self.no_pager |= config.no_pager.unwrap_or_default();
+
+        Ok(())
+    }
+
     pub fn do_versioning(
         &self,
         metadata: &Metadata,
         config: &WorkspaceConfig,
     ) -> Result<Option<(Option<Version>, Map<String, (Pkg, Version)>)>> {
+        match self.plan_release(metadata, config, false)? {
+            Some(release) => {
+                self.write_release(metadata, config, &release, false)?;
+                Ok(Some((release.new_version, release.new_versions)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Detect the changes and pick the new versions, `preview` skips the confirmations
+    pub fn plan_release(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        preview: bool,
+    ) -> Result<Option<Release>> {
+        self.plan_release_with(metadata, config, preview, prompt_pkgs)
+    }
+
+    /// Plan the release, with `pick` choosing the crates released by `--select`
+    fn plan_release_with(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        preview: bool,
+        pick: impl FnOnce(&[String], &[bool]) -> Result<Vec<usize>>,
+    ) -> Result<Option<Release>> {
         if self.git.no_git_commit
             && !(self.git.no_git_tag || (self.git.no_global_tag && self.git.no_individual_tags))
         {
             info!("tagging the current commit", "");
         }
 
+        let channels = self.channels(config)?;
+
         let last_tag = if !self.git.no_git {
             let change_data = ChangeData::new(metadata, &self.change)?;
 
-            if self.change.force.is_none() && change_data.count == "0" && !change_data.dirty {
+            let forced = self.change.force.is_some()
+                || self.graduate.is_some()
+                || self.promote.is_some()
+                || self.select;
+
+            if !forced && change_data.count == "0" && !change_data.dirty {
                 TERM_OUT.write_line("Current HEAD is already released, skipping versioning")?;
                 return Ok(None);
             }
@@ -126,15 +332,79 @@ impl VersionOpt {
             self.all,
         )?;
 
+        let changesets = Changesets::read(&metadata.workspace_root)?;
+        changesets.validate(
+            &metadata
+                .packages
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>(),
+        )?;
+
+        self.validate_overrides(metadata, config)?;
+
+        let pre = PreState::read(&metadata.workspace_root)?;
+        let mut hints = BumpHints {
+            changesets: changesets.bumps(),
+            dependents: HashMap::new(),
+            pre: pre.as_ref(),
+            channels,
+            metadata,
+            since: last_tag.as_deref(),
+            branch_policy: self
+                .git
+                .branch_policy(&metadata.workspace_root, config)?
+                .map(|(branch, policy)| (branch, policy.max_bump)),
+        };
+
+        // Crates named in changesets or overrides are released even when they haven't changed,
+        // and so are the prereleases graduating or being promoted
+        let (forced_p, mut rest_p) = unchanged_p.into_iter().partition::<Vec<_>, _>(|(_, p)| {
+            hints.changesets.contains_key(&p.name)
+                || self.bump_crate.iter().any(|(name, _)| name == &p.name)
+                || self.channel_bump(&[p], &p.version).is_some()
+                || graduation(hints.pre, &p.version).is_some()
+        });
+        changed_p.extend(forced_p);
+
+        if self.select {
+            select_pkgs(&mut changed_p, &mut rest_p, pick)?;
+        }
+
+        unchanged_p = fixed_mates(config, &mut changed_p, rest_p);
+
         if changed_p.is_empty() {
             TERM_OUT.write_line("No changes detected, skipping versioning")?;
             return Ok(None);
         }
 
+        // Crates sharing a version in a linked or fixed group are bumped from the highest
+        // version in their group, whether it changed or not. Other groups are bumped from
+        // the highest version of their changed crates.
+        let mut highest = HashMap::new();
+        for ((group_name, _), p) in changed_p.iter().chain(&unchanged_p) {
+            let linked = matches!(
+                config.explicit_policy(group_name),
+                Some(GroupPolicy::Linked | GroupPolicy::Fixed)
+            );
+
+            if linked && !p.config.independent.unwrap_or(false) {
+                let version = highest
+                    .entry(group_name.clone())
+                    .or_insert_with(|| p.version.clone());
+                if p.version > *version {
+                    *version = p.version.clone();
+                }
+            }
+        }
+
         let mut bumped_pkgs = HashMap::new();
 
         while !changed_p.is_empty() {
-            self.get_new_versions(metadata, changed_p, &mut bumped_pkgs)?;
+            self.get_new_versions(config, changed_p, &hints, &highest, &mut bumped_pkgs)?;
+
+            let dependent_bump = config.dependent_bump.unwrap_or_default();
+            let mut dependents = HashMap::new();
 
             let pkgs = unchanged_p.into_iter().partition::<Vec<_>, _>(|(_, p)| {
                 let pkg = metadata
@@ -143,23 +413,48 @@ impl VersionOpt {
                     .find(|x| x.name == p.name)
                     .expect(INTERNAL_ERR);
 
-                pkg.dependencies.iter().any(|x| {
-                    bumped_pkgs.values().any(|(_, _, new_versions)| {
-                        if let Some(version) = new_versions
-                            .iter()
-                            .find(|(p, _, _)| x.name == p.name)
-                            .map(|y| &y.1)
-                        {
-                            !x.req.matches(version) || is_unversioned(&x.req)
-                        } else {
-                            false
-                        }
+                let bumps = pkg
+                    .dependencies
+                    .iter()
+                    .filter_map(|x| {
+                        bumped_pkgs
+                            .values()
+                            .find_map(|(_, _, new_versions)| {
+                                new_versions.iter().find(|(p, _, _)| x.name == p.name)
+                            })
+                            .filter(|(_, version, _)| match dependent_bump {
+                                DependentBump::None => false,
+                                DependentBump::Patch | DependentBump::Match => true,
+                                DependentBump::OutOfRange => {
+                                    !x.req.matches(version) || is_unversioned(&x.req)
+                                }
+                            })
+                            .map(|(_, new_version, old_version)| {
+                                bump_level(old_version, new_version)
+                            })
                     })
-                })
+                    .collect::<Vec<_>>();
+
+                if bumps.is_empty() {
+                    return false;
+                }
+
+                let bump = match dependent_bump {
+                    DependentBump::Patch => Some(Bump::Patch),
+                    DependentBump::Match => bumps.into_iter().max_by_key(|x| x.selected()),
+                    _ => None,
+                };
+
+                if let Some(bump) = bump {
+                    dependents.insert(p.name.clone(), bump);
+                }
+
+                true
             });
 
+            hints.dependents = dependents;
             changed_p = pkgs.0;
-            unchanged_p = pkgs.1;
+            unchanged_p = fixed_mates(config, &mut changed_p, pkgs.1);
         }
 
         if bumped_pkgs.is_empty() {
@@ -202,10 +497,109 @@ impl VersionOpt {
             }
         }
 
-        self.alert_unversioned(unversioned_deps.into_iter().collect())?;
+        if !preview {
+            self.alert_unversioned(unversioned_deps.into_iter().collect())?;
+        }
+
+        let (new_version, new_versions) = self.confirm_versions(bumped_pkgs, preview)?;
+
+        Ok(Some(Release {
+            since: last_tag,
+            changesets,
+            pre,
+            new_version,
+            new_versions,
+        }))
+    }
+
+    /// Reset the last release commit and delete its tags, deleting the pushed tags with `remote`
+    pub fn undo(&self, metadata: &Metadata, config: &WorkspaceConfig, remote: bool) -> Result {
+        let root = &metadata.workspace_root;
+
+        let mut files = vec![root.join("Cargo.toml"), root.join("Cargo.lock")];
+
+        for (_, pkg) in get_group_packages(metadata, config, true)?.into_iter() {
+            let dir = pkg.manifest_path.parent().expect(INTERNAL_ERR);
+
+            files.extend(pkg.config.sync.iter().flatten().map(|x| dir.join(x)));
+            files.push(changelog_path(config, &pkg));
+            files.push(pkg.manifest_path);
+        }
+
+        if let Some(file) = config.changelog.as_ref().and_then(|x| x.root.as_ref()) {
+            files.push(root.join(file));
+        }
+
+        files.extend(config.version_files.iter().map(|x| root.join(&x.path)));
+
+        let files = files
+            .iter()
+            .filter_map(|x| x.strip_prefix(root).ok())
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+
+        // Changesets and the prerelease state are consumed by releases
+        self.git.undo(
+            root,
+            |file| {
+                files.iter().any(|x| x == file) || file.starts_with(&format!("{}/", CHANGESET_DIR))
+            },
+            remote,
+        )
+    }
+
+    /// Write the new versions to the manifests and commit them, `dry_run` only prints the changes
+    pub fn write_release(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        release: &Release,
+        dry_run: bool,
+    ) -> Result {
+        let Release {
+            new_version,
+            new_versions,
+            ..
+        } = release;
+
+        self.check_branch_policy(metadata, config, new_versions)?;
+
+        // Manifest contents before and after the edits, keyed by path
+        let mut manifests = Map::new();
+        let synced_files =
+            self.edit_release(metadata, config, new_version, new_versions, &mut manifests)?;
+
+        if dry_run {
+            return self.print_plan(metadata, &manifests, new_version, new_versions);
+        }
+
+        let index = self.git.index_tree(&metadata.workspace_root)?;
+        let mut transaction = Transaction::default();
+
+        if let Err(err) = self.apply_release(
+            metadata,
+            config,
+            release,
+            &manifests,
+            synced_files,
+            &mut transaction,
+        ) {
+            return rollback(metadata, &self.git, index.as_deref(), transaction, err);
+        }
 
-        let (new_version, new_versions) = self.confirm_versions(bumped_pkgs)?;
+        Ok(())
+    }
 
+    /// Edit the manifests and the files embedding the new versions in memory, returning
+    /// the files besides the manifests
+    fn edit_release(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        new_version: &Option<Version>,
+        new_versions: &Map<String, (Pkg, Version)>,
+        manifests: &mut Map<Utf8PathBuf, (String, String)>,
+    ) -> Result<Vec<Utf8PathBuf>> {
         let mut new_versions_root = Map::new();
 
         let workspace_root = metadata.workspace_root.join("Cargo.toml");
@@ -234,13 +628,13 @@ impl VersionOpt {
                 continue;
             }
 
+            // Build metadata is ignored in requirements, so it's left out of them
             let mut new_versions_sub = deps
                 .into_iter()
                 .map(|(key, pkg_name)| {
-                    (
-                        key,
-                        new_versions.get(&pkg_name).expect(INTERNAL_ERR).1.clone(),
-                    )
+                    let mut version = new_versions.get(&pkg_name).expect(INTERNAL_ERR).1.clone();
+                    version.build.clear();
+                    (key, version)
                 })
                 .collect::<Map<_, _>>();
 
@@ -255,20 +649,18 @@ impl VersionOpt {
 
             let mut inherited_pkgs = HashSet::new();
 
-            fs::write(
-                &p.manifest_path,
-                format!(
-                    "{}\n",
-                    change_versions(
-                        fs::read_to_string(&p.manifest_path)?,
-                        &p.name,
-                        &new_versions_sub,
-                        ManifestDiscriminant::Package,
-                        self.exact,
-                        &mut inherited_pkgs,
-                    )?
-                ),
-            )?;
+            let manifest = edit_manifest(manifests, &p.manifest_path)?;
+            *manifest = format!(
+                "{}\n",
+                change_versions(
+                    manifest.clone(),
+                    &p.name,
+                    &new_versions_sub,
+                    ManifestDiscriminant::Package,
+                    self.exact,
+                    &mut inherited_pkgs,
+                )?
+            );
 
             new_versions_root.extend(inherited_pkgs.into_iter().filter_map(|pkg_name| {
                 new_versions_sub
@@ -281,51 +673,435 @@ impl VersionOpt {
             new_versions_root.insert(workspace_key.clone(), version.clone());
         }
 
-        fs::write(
-            &workspace_root,
-            format!(
-                "{}\n",
-                change_versions(
-                    fs::read_to_string(&workspace_root)?,
-                    &workspace_key,
-                    &new_versions_root,
-                    ManifestDiscriminant::Workspace,
-                    self.exact,
-                    &mut HashSet::new(),
-                )?
-            ),
+        let manifest = edit_manifest(manifests, &workspace_root)?;
+        *manifest = format!(
+            "{}\n",
+            change_versions(
+                manifest.clone(),
+                &workspace_key,
+                &new_versions_root,
+                ManifestDiscriminant::Workspace,
+                self.exact,
+                &mut HashSet::new(),
+            )?
+        );
+
+        // Files besides the manifests which embed the new versions
+        let mut synced_files = vec![];
+
+        for (p, version) in new_versions.values() {
+            for file in p.config.sync.iter().flatten() {
+                let path = p.manifest_path.parent().expect(INTERNAL_ERR).join(file);
+
+                if !path.is_file() {
+                    return Err(Error::BadSyncFile {
+                        path: path.to_string(),
+                        msg: "file not found".to_string(),
+                    });
+                }
+
+                let contents = edit_manifest(manifests, &path)?;
+                *contents = sync_version(&path, contents, version)?;
+
+                synced_files.push(path);
+            }
+        }
+
+        for spec in &config.version_files {
+            let version = match &spec.krate {
+                Some(name) => {
+                    if !metadata.packages.iter().any(|p| &p.name == name) {
+                        return Err(Error::PackageNotFound { id: name.clone() });
+                    }
+
+                    new_versions.get(name).map(|(_, version)| version)
+                }
+                None => new_version.as_ref(),
+            };
+
+            // Files of crates that aren't released are left as is
+            if let Some(version) = version {
+                let path = metadata.workspace_root.join(&spec.path);
+
+                if !path.is_file() {
+                    return Err(Error::BadVersionFile {
+                        path: spec.path.clone(),
+                        msg: "file not found".to_string(),
+                    });
+                }
+
+                let contents = edit_manifest(manifests, &path)?;
+                *contents = spec.sync(contents, version)?;
+
+                synced_files.push(path);
+            }
+        }
+
+        Ok(synced_files)
+    }
+
+    /// Refuse releases larger than the policy of the current branch allows
+    fn check_branch_policy(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Result {
+        let (branch, policy) = match self.git.branch_policy(&metadata.workspace_root, config)? {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        for (name, (pkg, version)) in new_versions {
+            if !release_allowed(policy.max_bump, &pkg.version, version) {
+                return Err(branch_policy_error(&branch, policy.max_bump, name, version));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rewrite the changed crates to prereleases traceable to the current commit,
+    /// without committing, to be undone by `restore`
+    pub fn snapshot(&self, metadata: &Metadata, config: &WorkspaceConfig) -> Result {
+        let state = snapshot_path(metadata);
+
+        if state.is_file() {
+            return Err(Error::SnapshotExists(state.to_string()));
+        }
+
+        let change_data = ChangeData::new(metadata, &self.change)?;
+        let (changed, _) = self.change.get_changed_pkgs(
+            metadata,
+            config,
+            &change_data.since,
+            &self.groups[..],
+            self.all,
         )?;
 
-        for (pkg_name, (p, _)) in &new_versions {
+        let new_versions = changed
+            .into_iter()
+            .filter(|((group_name, _), _)| group_name != &GroupName::Excluded)
+            .map(|(_, p)| {
+                let version = snapshot_version(&p.version, &change_data);
+                (p.name.clone(), (p, version))
+            })
+            .collect::<Map<_, _>>();
+
+        if new_versions.is_empty() {
+            TERM_OUT.write_line("No changes detected, skipping snapshot")?;
+            return Ok(());
+        }
+
+        self.rewrite_restorable(metadata, config, &new_versions)?;
+
+        for (name, (_, version)) in &new_versions {
+            info!("snapshot", format!("{} {}", name, version));
+        }
+
+        Ok(())
+    }
+
+    /// Versions of the changed crates and all their dependents as prereleases of the
+    /// current commit, for publishing canaries
+    pub fn canary_versions(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        preid: &str,
+    ) -> Result<Map<String, (Pkg, Version)>> {
+        let change_data = ChangeData::new(metadata, &self.change)?;
+        let (changed, unchanged) = self.change.get_changed_pkgs(
+            metadata,
+            config,
+            &change_data.since,
+            &self.groups[..],
+            self.all,
+        )?;
+
+        let not_excluded =
+            |((group_name, _), _): &((GroupName, _), Pkg)| group_name != &GroupName::Excluded;
+
+        let mut canaries = changed
+            .into_iter()
+            .filter(not_excluded)
+            .map(|(_, p)| p)
+            .collect::<Vec<_>>();
+        let mut rest = unchanged
+            .into_iter()
+            .filter(not_excluded)
+            .map(|(_, p)| p)
+            .collect::<Vec<_>>();
+
+        // Dependents get a canary too, since they have to require the new versions
+        loop {
+            let (dependents, others) = rest.into_iter().partition::<Vec<_>, _>(|p| {
+                metadata
+                    .packages
+                    .iter()
+                    .find(|x| x.id == p.id)
+                    .expect(INTERNAL_ERR)
+                    .dependencies
+                    .iter()
+                    .any(|d| d.path.is_some() && canaries.iter().any(|c| c.name == d.name))
+            });
+
+            rest = others;
+
+            if dependents.is_empty() {
+                break;
+            }
+
+            canaries.extend(dependents);
+        }
+
+        Ok(canaries
+            .into_iter()
+            .map(|p| {
+                let version = canary_version(&p.version, preid, &change_data.sha);
+                (p.name.clone(), (p, version))
+            })
+            .collect())
+    }
+
+    /// Write the new versions to the manifests and the lockfile without committing,
+    /// returning the changes to be rolled back afterwards
+    pub fn rewrite(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Result<Transaction> {
+        let mut manifests = Map::new();
+        self.edit_release(metadata, config, &None, new_versions, &mut manifests)?;
+
+        let mut transaction = Transaction::default();
+
+        let written = transaction
+            .write_all(
+                manifests
+                    .iter()
+                    .filter(|(_, (old, new))| old != new)
+                    .map(|(path, (_, new))| (path.as_path(), new.as_str())),
+            )
+            .and_then(|_| self.update_lock(metadata, new_versions, &mut transaction));
+
+        match written {
+            Ok(()) => Ok(transaction),
+            Err(err) => rollback(metadata, &self.git, None, transaction, err),
+        }
+    }
+
+    /// Rewrite the versions like `rewrite`, keeping the changes so `restore` can put them
+    /// back if the rewrite is interrupted or meant to be kept for a while
+    pub fn rewrite_restorable(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Result<Transaction> {
+        let state = snapshot_path(metadata);
+
+        if state.is_file() {
+            return Err(Error::SnapshotExists(state.to_string()));
+        }
+
+        let transaction = self.rewrite(metadata, config, new_versions)?;
+
+        fs::create_dir_all(state.parent().expect(INTERNAL_ERR))?;
+        fs::write(&state, serde_json::to_string(&transaction)?)?;
+
+        Ok(transaction)
+    }
+
+    /// Forget the changes kept by `rewrite_restorable`, once they were put back
+    pub fn forget_restorable(&self, metadata: &Metadata) -> Result {
+        fs::remove_file(snapshot_path(metadata))?;
+
+        Ok(())
+    }
+
+    /// Put back the files rewritten by `snapshot`, or by an interrupted canary
+    pub fn restore(&self, metadata: &Metadata) -> Result {
+        let state = snapshot_path(metadata);
+
+        if !state.is_file() {
+            return Err(Error::NoSnapshot);
+        }
+
+        let transaction: Transaction = serde_json::from_str(&fs::read_to_string(&state)?)?;
+        transaction.rollback()?;
+
+        fs::remove_file(&state)?;
+
+        Ok(())
+    }
+
+    /// Write the edited files, update the lockfile and commit, leaving the changes to
+    /// be rolled back by the caller on failure
+    fn apply_release(
+        &self,
+        metadata: &Metadata,
+        config: &WorkspaceConfig,
+        release: &Release,
+        manifests: &Map<Utf8PathBuf, (String, String)>,
+        synced_files: Vec<Utf8PathBuf>,
+        transaction: &mut Transaction,
+    ) -> Result {
+        let Release {
+            changesets,
+            pre,
+            new_version,
+            new_versions,
+            ..
+        } = release;
+
+        transaction.write_all(
+            manifests
+                .iter()
+                .filter(|(_, (old, new))| old != new)
+                .map(|(path, (_, new))| (path.as_path(), new.as_str())),
+        )?;
+
+        self.update_lock(metadata, new_versions, transaction)?;
+
+        let changelogs = write_changelogs(metadata, config, &self.git, release, transaction)?;
+
+        changesets.consume(|name| new_versions.contains_key(name), transaction)?;
+
+        if let Some(PreState {
+            mode: PreMode::Exit,
+            ..
+        }) = pre
+        {
+            PreState::remove(&metadata.workspace_root, transaction)?;
+        }
+
+        self.git.commit(
+            &metadata.workspace_root,
+            new_version,
+            new_versions,
+            &[changelogs, synced_files].concat(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Rewrite the versions of the members in `Cargo.lock` in one go, only resolving
+    /// the lockfile with a single `cargo update` if the rewritten one isn't up to date
+    fn update_lock(
+        &self,
+        metadata: &Metadata,
+        new_versions: &Map<String, (Pkg, Version)>,
+        transaction: &mut Transaction,
+    ) -> Result {
+        let root = &metadata.workspace_root;
+        let lock_path = root.join("Cargo.lock");
+
+        if lock_path.is_file() {
+            let lock = fs::read_to_string(&lock_path)?;
+            transaction.write(
+                &lock_path,
+                &change_lock_versions(&lock, &lock_versions(new_versions)),
+            )?;
+
             let output = cargo(
-                &metadata.workspace_root,
-                &[
-                    "update",
-                    "-p",
-                    &format!(
-                        "file://{}#{}",
-                        p.manifest_path.parent().expect(INTERNAL_ERR),
-                        pkg_name
-                    ),
-                ],
+                root,
+                &["metadata", "--locked", "--format-version", "1"],
                 &[],
             )?;
 
-            if output.1.contains("error:") {
-                return Err(Error::Update);
+            if !output.1.contains("error:") {
+                return Ok(());
+            }
+
+            info!("lockfile", "needs to be resolved, running cargo update");
+        } else {
+            transaction.track(&lock_path)?;
+        }
+
+        let specs = new_versions
+            .iter()
+            .map(|(name, (p, _))| {
+                format!(
+                    "file://{}#{}",
+                    p.manifest_path.parent().expect(INTERNAL_ERR),
+                    name
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Only the members are named, but resolving them can still move other entries of the
+        // lockfile, like registry dependencies whose requirements changed with the new versions
+        let mut args = vec!["update"];
+        for spec in &specs {
+            args.extend(["-p", spec.as_str()]);
+        }
+
+        let output = cargo(root, &args, &[])?;
+
+        if output.1.contains("error:") {
+            return Err(Error::Update);
+        }
+
+        Ok(())
+    }
+
+    fn print_plan(
+        &self,
+        metadata: &Metadata,
+        manifests: &Map<Utf8PathBuf, (String, String)>,
+        new_version: &Option<Version>,
+        new_versions: &Map<String, (Pkg, Version)>,
+    ) -> Result {
+        let root = &metadata.workspace_root;
+
+        for (path, (old, new)) in manifests {
+            print_diff(path.strip_prefix(root).unwrap_or(path), old, new)?;
+        }
+
+        let lock_path = root.join("Cargo.lock");
+        if lock_path.is_file() {
+            let lock = fs::read_to_string(&lock_path)?;
+
+            print_diff(
+                Utf8Path::new("Cargo.lock"),
+                &lock,
+                &change_lock_versions(&lock, &lock_versions(new_versions)),
+            )?;
+        }
+
+        if self.git.no_git || self.git.no_git_commit {
+            TERM_OUT.write_line("Would not commit")?;
+        } else if let Some(msg) = self.git.commit_message(new_version, new_versions) {
+            TERM_OUT.write_line("Would commit with message:")?;
+            for line in msg.lines() {
+                TERM_OUT.write_line(format!("    {}", line).trim_end())?;
             }
+        } else {
+            TERM_OUT.write_line("Would amend the current commit")?;
         }
 
-        self.git
-            .commit(&metadata.workspace_root, &new_version, &new_versions)?;
+        let tags = self.git.tag_names(new_version, new_versions);
+
+        if tags.is_empty() {
+            TERM_OUT.write_line("\nWould not create any tags")?;
+        } else {
+            TERM_OUT.write_line("\nWould create tags:")?;
+            for tag in tags {
+                TERM_OUT.write_line(&format!(" - {}", style(tag).yellow()))?;
+            }
+        }
 
-        Ok(Some((new_version, new_versions)))
+        Ok(())
     }
 
     fn get_new_versions(
         &self,
-        metadata: &Metadata,
+        config: &WorkspaceConfig,
         pkgs: Vec<((GroupName, Option<Version>), Pkg)>,
+        hints: &BumpHints,
+        highest: &HashMap<GroupName, Version>,
         bumped_pkgs: &mut HashMap<
             GroupName,
             (
@@ -362,39 +1138,44 @@ impl VersionOpt {
                     }
                 }
             };
-            let (independent_pkgs, same_pkgs) = pkgs
-                .into_iter()
-                .partition::<Vec<_>, _>(|p| p.config.independent.unwrap_or(false));
+            let independent_group = config.policy(&group_name) == GroupPolicy::Independent;
+            let (independent_pkgs, same_pkgs) = pkgs.into_iter().partition::<Vec<_>, _>(|p| {
+                independent_group || p.config.independent.unwrap_or(false)
+            });
 
             if !same_pkgs.is_empty() {
                 let group_version = match group_ver {
                     Some(ver) => ver,
-                    None => {
-                        let mut group_version = same_pkgs
-                            .iter()
-                            .map(|p| {
-                                &metadata
-                                    .packages
+                    // Crates reached by a later round follow the version picked for their group
+                    None => match new_group_version {
+                        Some(version) => version.clone(),
+                        None => {
+                            let group_version = highest.get(&group_name).unwrap_or_else(|| {
+                                same_pkgs
                                     .iter()
-                                    .find(|x| x.id == p.id)
+                                    .map(|p| &p.version)
+                                    .max()
                                     .expect(INTERNAL_ERR)
-                                    .version
-                            })
-                            .max()
-                            .expect(INTERNAL_ERR)
-                            .clone();
-                        if common_version.is_none() {
+                            });
+                            let spec = self.group_bump(
+                                config,
+                                &group_name,
+                                &same_pkgs,
+                                group_version,
+                                hints,
+                            )?;
                             let custom_group_version = self.ask_version(
-                                &group_version,
+                                group_version,
                                 &group_name,
                                 Some(&same_pkgs[..]),
                                 None,
+                                spec.as_ref(),
+                                hints,
                             )?;
-                            *common_version = Some(group_version);
-                            group_version = custom_group_version;
+                            *common_version = Some(group_version.clone());
+                            custom_group_version
                         }
-                        group_version
-                    }
+                    },
                 };
 
                 if let None = new_group_version {
@@ -409,21 +1190,212 @@ impl VersionOpt {
                 }
             }
 
-            for p in independent_pkgs {
-                let old_version = p.version.clone();
-                let new_version =
-                    self.ask_version(&old_version, &group_name, None, Some(&p.name))?;
-                if old_version != new_version {
-                    new_versions.push((p, new_version, old_version));
-                }
-            }
+            for p in independent_pkgs {
+                let old_version = p.version.clone();
+                let spec = self.crate_bump(config, &group_name, &p, hints)?;
+                let new_version = self.ask_version(
+                    &old_version,
+                    &group_name,
+                    None,
+                    Some(&p.name),
+                    spec.as_ref(),
+                    hints,
+                )?;
+                if old_version != new_version {
+                    new_versions.push((p, new_version, old_version));
+                }
+            }
+
+            if new_versions.is_empty() {
+                bumped_pkgs.remove(&group_name);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_overrides(&self, metadata: &Metadata, config: &WorkspaceConfig) -> Result {
+        for (i, (name, _)) in self.bump_crate.iter().enumerate() {
+            if !metadata.packages.iter().any(|p| &p.name == name) {
+                return Err(Error::PackageNotFound { id: name.clone() });
+            }
+
+            if self.bump_crate[..i].iter().any(|(x, _)| x == name) {
+                return Err(Error::DuplicateBump { name: name.clone() });
+            }
+        }
+
+        for (i, (group, _)) in self.bump_group.iter().enumerate() {
+            if self.bump_group[..i].iter().any(|(x, _)| x == group) {
+                return Err(Error::DuplicateBump {
+                    name: group.to_string(),
+                });
+            }
+
+            let exists = match group {
+                GroupName::Custom(name) => config.groups.iter().any(|x| &x.name == name),
+                _ => true,
+            };
+
+            if !exists {
+                return Err(Error::GroupNotFound {
+                    name: group.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prerelease channels from the config, checking `--promote` against them
+    fn channels(&self, config: &WorkspaceConfig) -> Result<Vec<String>> {
+        let channels = config.prerelease_channels.clone().unwrap_or_else(|| {
+            DEFAULT_CHANNELS
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        });
+
+        if let Some((from, to)) = &self.promote {
+            let position = |name: &String| {
+                channels
+                    .iter()
+                    .position(|x| x == name)
+                    .ok_or_else(|| Error::UnknownChannel {
+                        name: name.clone(),
+                        channels: channels.clone(),
+                    })
+            };
+
+            if position(to)? <= position(from)? {
+                return Err(Error::BackwardPromotion {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+
+        Ok(channels)
+    }
+
+    /// Version asked for by `--graduate` or `--promote`, if any of the crates is selected
+    fn channel_bump(&self, pkgs: &[&Pkg], version: &Version) -> Option<BumpSpec> {
+        if let Some(patterns) = &self.graduate {
+            let selected = patterns.is_empty()
+                || pkgs.iter().any(|p| {
+                    patterns
+                        .iter()
+                        .any(|x| x.compile_matcher().is_match(&p.name))
+                });
+
+            if selected && !version.pre.is_empty() {
+                return Some(BumpSpec::Version(stable(version)));
+            }
+        }
+
+        match &self.promote {
+            Some((from, to)) if channel(version) == Some(from) => {
+                Some(BumpSpec::Version(on_channel(version, to)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Bump for crates sharing a version, which must not be given conflicting overrides
+    fn group_bump(
+        &self,
+        config: &WorkspaceConfig,
+        group: &GroupName,
+        pkgs: &[Pkg],
+        version: &Version,
+        hints: &BumpHints,
+    ) -> Result<Option<BumpSpec>> {
+        let mut specs = self
+            .bump_group
+            .iter()
+            .filter(|(name, _)| name == group)
+            .map(|(name, spec)| (format!("group {}", name), spec))
+            .chain(
+                self.bump_crate
+                    .iter()
+                    .filter(|(name, _)| pkgs.iter().any(|p| &p.name == name))
+                    .map(|(name, spec)| (name.clone(), spec)),
+            )
+            .collect::<Vec<_>>();
+
+        if let Some((_, spec)) = specs.first() {
+            if specs.iter().any(|(_, x)| x != spec) {
+                specs.sort_by(|a, b| a.0.cmp(&b.0));
 
-            if new_versions.is_empty() {
-                bumped_pkgs.remove(&group_name);
+                return Err(Error::ConflictingBumps {
+                    group: group.to_string(),
+                    sources: specs.into_iter().map(|(name, _)| name).collect(),
+                });
             }
+
+            return Ok(Some((*spec).clone()));
         }
 
-        Ok(())
+        if let Some(format) = config.calver(group, None)? {
+            return Ok(Some(BumpSpec::Version(format.next(version, &today())?)));
+        }
+
+        Ok(self
+            .channel_bump(&pkgs.iter().collect::<Vec<_>>(), version)
+            .or_else(|| {
+                pkgs.iter()
+                    .filter_map(|p| hints.changesets.get(&p.name))
+                    .max_by_key(|x| x.selected())
+                    .or_else(|| {
+                        pkgs.iter()
+                            .filter_map(|p| hints.dependents.get(&p.name))
+                            .max_by_key(|x| x.selected())
+                    })
+                    .map(|x| BumpSpec::Bump(x.clone()))
+            })
+            .or_else(|| graduation(hints.pre, version)))
+    }
+
+    /// Bump for an independent crate, its own override wins over its group's
+    fn crate_bump(
+        &self,
+        config: &WorkspaceConfig,
+        group: &GroupName,
+        pkg: &Pkg,
+        hints: &BumpHints,
+    ) -> Result<Option<BumpSpec>> {
+        let spec = self
+            .bump_crate
+            .iter()
+            .find(|(x, _)| x == &pkg.name)
+            .map(|(_, spec)| spec)
+            .or_else(|| {
+                self.bump_group
+                    .iter()
+                    .find(|(x, _)| x == group)
+                    .map(|(_, spec)| spec)
+            });
+
+        if let Some(spec) = spec {
+            return Ok(Some(spec.clone()));
+        }
+
+        if let Some(format) = config.calver(group, Some(&pkg.config))? {
+            return Ok(Some(BumpSpec::Version(
+                format.next(&pkg.version, &today())?,
+            )));
+        }
+
+        Ok(self
+            .channel_bump(&[pkg], &pkg.version)
+            .or_else(|| {
+                hints
+                    .changesets
+                    .get(&pkg.name)
+                    .or_else(|| hints.dependents.get(&pkg.name))
+                    .map(|x| BumpSpec::Bump(x.clone()))
+            })
+            .or_else(|| graduation(hints.pre, &pkg.version)))
     }
 
     fn alert_unversioned(
@@ -439,6 +1411,14 @@ impl VersionOpt {
         }
 
         let len_unversioned = pkgs.len();
+
+        interactive(
+            &format!(
+                "You have {} packages with unversioned dependencies",
+                len_unversioned
+            ),
+            "`--yes`",
+        )?;
         let mut pkgs = Some(&pkgs);
         let selected = loop {
             let items: Vec<_> = if let Some(_) = pkgs {
@@ -532,6 +1512,7 @@ impl VersionOpt {
                 Vec<(Pkg, Version, Version)>,
             ),
         >,
+        preview: bool,
     ) -> Result<(Option<Version>, Map<String, (Pkg, Version)>)> {
         let mut new_versions = Map::new();
 
@@ -573,11 +1554,15 @@ impl VersionOpt {
         TERM_ERR.write_line("")?;
         TERM_ERR.flush()?;
 
-        let create = self.yes
-            || Confirm::with_theme(&ColorfulTheme::default())
-                .with_prompt("Are you sure you want to create these versions?")
+        let create = self.yes || preview || {
+            let prompt = "Are you sure you want to create these versions?";
+            interactive(prompt, "`--yes`")?;
+
+            Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt)
                 .default(false)
-                .interact_on(&TERM_ERR)?;
+                .interact_on(&TERM_ERR)?
+        };
 
         if !create {
             exit(0);
@@ -590,13 +1575,55 @@ impl VersionOpt {
         &self,
         cur_version: &Version,
         group: &GroupName,
-        mut group_pkgs: Option<&[Pkg]>,
+        group_pkgs: Option<&[Pkg]>,
         pkg_name: Option<&str>,
+        spec: Option<&BumpSpec>,
+        hints: &BumpHints,
     ) -> Result<Version> {
+        let subject = match (group, pkg_name) {
+            (_, Some(name)) => name.to_string(),
+            (GroupName::Custom(group_name), None) => format!("the group `{}`", group_name),
+            (_, None) => "the workspace".to_string(),
+        };
+
+        // Refuse a version past the policy of the branch before asking about the next one
+        let check = |version: Version| match &hints.branch_policy {
+            Some((branch, max_bump)) if !release_allowed(*max_bump, cur_version, &version) => {
+                Err(branch_policy_error(branch, *max_bump, &subject, &version))
+            }
+            _ => Ok(version),
+        };
+
+        let hint = match spec {
+            Some(BumpSpec::Version(version)) => return check(version.clone()),
+            Some(BumpSpec::Bump(bump)) => Some(bump),
+            None => None,
+        };
+
         let mut items = version_items(cur_version, &self.pre_id);
 
+        // In prerelease mode, the stable bumps become prereleases of the tag
+        if let Some(
+            pre @ PreState {
+                mode: PreMode::Pre, ..
+            },
+        ) = hints.pre
+        {
+            for (item, version) in items.iter_mut().take(3) {
+                if let Some(version) = version {
+                    *version = pre.prerelease(cur_version, version);
+                    *item = format!(
+                        "{} ({})",
+                        item.split(' ').next().expect(INTERNAL_ERR),
+                        version
+                    );
+                }
+            }
+        }
+
         items.push(("Custom Prerelease".to_string(), None));
         items.push(("Custom Version".to_string(), None));
+        items.extend(channel_items(cur_version, &hints.channels));
 
         let prompt = match (group, pkg_name) {
             (GroupName::Custom(group_name), Some(name)) => {
@@ -611,20 +1638,61 @@ impl VersionOpt {
 
         let theme = ColorfulTheme::default();
 
-        let selected = if let Some(bump) = &self.bump {
+        let selected = if let Some(bump) = hint.or(self.bump.as_ref()) {
             bump.selected()
         } else {
-            loop {
-                let items = items.iter().map(|x| x.0.as_str());
+            interactive(
+                &format!("Select a new version {}", prompt.trim_end()),
+                "a bump (e.g. `cargo ws version patch`), `--bump-crate`, `--bump-group` or changesets",
+            )?;
 
-                let items: Vec<_> = if let Some(_) = group_pkgs {
-                    Some("List Packages Affected")
-                        .into_iter()
-                        .chain(items)
-                        .collect()
-                } else {
-                    items.collect()
-                };
+            // Listings offered above the versions, as (item, title, lines)
+            let mut views = vec![];
+
+            if let Some(group_pkgs) = group_pkgs {
+                let width = (group_pkgs.len() as f32).log10() as usize + 1;
+
+                views.push((
+                    "List Packages Affected".to_string(),
+                    format!("{} packages affected in this group", group_pkgs.len()),
+                    group_pkgs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, p)| {
+                            format!(
+                                " {:>s$} │ {}: {}",
+                                i + 1,
+                                style(&p.name).yellow().for_stderr(),
+                                p.version,
+                                s = width,
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                ));
+            }
+
+            let names = match (group_pkgs, pkg_name) {
+                (Some(group_pkgs), _) => group_pkgs.iter().map(|p| p.name.as_str()).collect(),
+                (None, Some(name)) => vec![name],
+                (None, None) => vec![],
+            };
+
+            if let Some(view) = self.history_view(hints, &names)? {
+                views.push(view);
+            }
+
+            // Only the versions the branch policy allows are offered, besides the custom ones
+            let offered = match &hints.branch_policy {
+                Some((_, max_bump)) => allowed_items(&items, cur_version, *max_bump),
+                None => (0..items.len()).collect(),
+            };
+
+            loop {
+                let items = views
+                    .iter()
+                    .map(|x| x.0.as_str())
+                    .chain(offered.iter().map(|i| items[*i].0.as_str()))
+                    .collect::<Vec<_>>();
 
                 match Select::with_theme(&theme)
                     .with_prompt(&format!(
@@ -636,56 +1704,26 @@ impl VersionOpt {
                     .interact_on_opt(&TERM_ERR)?
                 {
                     None => exit(0),
-                    Some(mut selected) => {
-                        if let Some(group_pkgs) = if self.no_pager {
-                            // take, so we only get the list option once
-                            group_pkgs.take()
-                        } else {
-                            group_pkgs
-                        } {
-                            if selected == 0 {
-                                if self.no_pager {
-                                    for (i, p) in group_pkgs.iter().enumerate() {
-                                        TERM_ERR.write_line(&format!(
-                                            " {:>s$} │ {}: {}",
-                                            i + 1,
-                                            style(&p.name).yellow().for_stderr(),
-                                            p.version,
-                                            s = (group_pkgs.len() as f32).log10() as usize + 1,
-                                        ))?;
-                                    }
-                                } else {
-                                    let group_pkgs = group_pkgs
-                                        .iter()
-                                        .enumerate()
-                                        .map(|(i, p)| {
-                                            format!(
-                                                " {:>s$} │ {}: {}",
-                                                i + 1,
-                                                style(&p.name).yellow().for_stderr(),
-                                                p.version,
-                                                s = (group_pkgs.len() as f32).log10() as usize + 1,
-                                            )
-                                        })
-                                        .collect::<Vec<_>>();
-                                    Select::new()
-                                        .with_prompt(format!(
-                                            "{} packages affected in this group",
-                                            group_pkgs.len()
-                                        ))
-                                        .items(&group_pkgs)
-                                        .default(0)
-                                        .clear(true)
-                                        .report(false)
-                                        .max_length(10)
-                                        .interact_on_opt(&TERM_ERR)?;
-                                }
-                                continue;
+                    Some(selected) if selected < views.len() => {
+                        if self.no_pager {
+                            // remove, so we only get each listing once
+                            let (_, _, lines) = views.remove(selected);
+                            for line in lines {
+                                TERM_ERR.write_line(&line)?;
                             }
-                            selected -= 1;
+                        } else {
+                            let (_, title, lines) = &views[selected];
+                            Select::new()
+                                .with_prompt(title)
+                                .items(lines)
+                                .default(0)
+                                .clear(true)
+                                .report(false)
+                                .max_length(10)
+                                .interact_on_opt(&TERM_ERR)?;
                         }
-                        break selected;
                     }
+                    Some(selected) => break offered[selected - views.len()],
                 };
             }
         };
@@ -696,6 +1734,8 @@ impl VersionOpt {
             let preid = if let Some(preid) = &self.pre_id {
                 preid.clone()
             } else {
+                interactive("Enter a prerelease identifier", "`--pre-id`")?;
+
                 Input::with_theme(&theme)
                     .with_prompt(&format!(
                         "Enter a prerelease identifier (default: '{}', yielding {})",
@@ -710,21 +1750,368 @@ impl VersionOpt {
             if let Some(version) = &self.custom {
                 version.clone()
             } else {
+                interactive(
+                    "Enter a custom version",
+                    "`cargo ws version custom <version>`",
+                )?;
+
                 Input::with_theme(&theme)
                     .with_prompt("Enter a custom version")
                     .interact_on(&TERM_ERR)?
             }
         } else {
+            // Graduating or promoting a version that isn't a prerelease leaves it as is
             items
                 .get(selected)
-                .expect(INTERNAL_ERR)
-                .clone()
-                .1
-                .expect(INTERNAL_ERR)
+                .and_then(|x| x.1.clone())
+                .unwrap_or_else(|| cur_version.clone())
+        };
+
+        check(new_version)
+    }
+
+    /// Listing of the commits that touched the crates since the last release, highlighting
+    /// the breaking ones, followed by their diffstat
+    fn history_view(
+        &self,
+        hints: &BumpHints,
+        names: &[&str],
+    ) -> Result<Option<(String, String, Vec<String>)>> {
+        if self.git.no_git || names.is_empty() {
+            return Ok(None);
+        }
+
+        let metadata = hints.metadata;
+        let root = &metadata.workspace_root;
+
+        let paths = metadata
+            .packages
+            .iter()
+            .filter(|p| names.contains(&p.name.as_str()))
+            .filter_map(|p| p.manifest_path.parent()?.strip_prefix(root).ok())
+            .map(|dir| match dir.as_str() {
+                "" => ".".to_string(),
+                _ => slash_path(dir),
+            })
+            .collect::<Vec<_>>();
+        let paths = paths.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+        let exclude = nested_paths(&member_paths(metadata), &paths);
+
+        let commits = commits(root, hints.since, &paths, &exclude)?;
+
+        if commits.is_empty() {
+            return Ok(None);
+        }
+
+        let mut lines = commits
+            .iter()
+            .map(|c| {
+                let sha = style(c.short_sha()).dim().for_stderr();
+
+                if c.breaking {
+                    format!(
+                        " {} {} {}",
+                        sha,
+                        style(c.headline()).red().for_stderr(),
+                        style("BREAKING").red().bold().for_stderr()
+                    )
+                } else {
+                    format!(" {} {}", sha, c.headline())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(since) = hints.since {
+            lines.push(String::new());
+            lines.extend(
+                diffstat(root, since, &paths, &exclude)?
+                    .into_iter()
+                    .map(|x| format!(" {}", x)),
+            );
+        }
+
+        let breaking = commits.iter().filter(|x| x.breaking).count();
+        let item = match breaking {
+            0 => format!("List Commits ({})", commits.len()),
+            _ => format!("List Commits ({}, {} breaking)", commits.len(), breaking),
+        };
+        let title = match hints.since {
+            Some(since) => format!("{} commits since {}", commits.len(), since),
+            None => format!("{} commits", commits.len()),
+        };
+
+        Ok(Some((item, title, lines)))
+    }
+}
+
+/// Restore the files changed by a failed release, and the index to the given tree, and
+/// pass its error on
+fn rollback<T>(
+    metadata: &Metadata,
+    git: &GitOpt,
+    index: Option<&str>,
+    transaction: Transaction,
+    err: Error,
+) -> Result<T> {
+    info!("rolling back", "restoring the files changed by the release");
+
+    if let Err(rollback_err) = transaction.rollback() {
+        err.print()?;
+        return Err(rollback_err);
+    }
+
+    git.unstage(&metadata.workspace_root, index)?;
+
+    Err(err)
+}
+
+/// Where `version --snapshot` and `publish --canary` keep the files they rewrote
+fn snapshot_path(metadata: &Metadata) -> Utf8PathBuf {
+    metadata
+        .target_directory
+        .join("cargo-workspaces")
+        .join(SNAPSHOT_FILE)
+}
+
+/// The next patch, or the stable version of a prerelease, as a `dev` prerelease counting the
+/// commits since the last release, with the commit in the build metadata
+fn snapshot_version(cur_version: &Version, change_data: &ChangeData) -> Version {
+    let mut version = if cur_version.is_prerelease() {
+        stable(cur_version)
+    } else {
+        Version::new(cur_version.major, cur_version.minor, cur_version.patch + 1)
+    };
+
+    version.pre = vec![
+        Identifier::AlphaNumeric("dev".to_string()),
+        Identifier::Numeric(change_data.count.parse().unwrap_or_default()),
+    ];
+    version.build = vec![Identifier::AlphaNumeric(format!("g{}", change_data.sha))];
+
+    if change_data.dirty {
+        version
+            .build
+            .push(Identifier::AlphaNumeric("dirty".to_string()));
+    }
+
+    version
+}
+
+/// The next patch, or the stable version of a prerelease, as a prerelease of the commit
+/// on the given channel
+fn canary_version(cur_version: &Version, preid: &str, sha: &str) -> Version {
+    let mut version = if cur_version.is_prerelease() {
+        stable(cur_version)
+    } else {
+        Version::new(cur_version.major, cur_version.minor, cur_version.patch + 1)
+    };
+
+    // An all digit sha would be a numeric identifier, which can't have leading zeroes
+    let sha = if sha.bytes().all(|x| x.is_ascii_digit()) {
+        format!("g{}", sha)
+    } else {
+        sha.to_string()
+    };
+
+    version.pre = vec![
+        Identifier::AlphaNumeric(preid.to_string()),
+        Identifier::AlphaNumeric(sha),
+    ];
+
+    version
+}
+
+/// Old and new versions of the released crates, as expected by `change_lock_versions`
+fn lock_versions(new_versions: &Map<String, (Pkg, Version)>) -> Map<String, (Version, Version)> {
+    new_versions
+        .iter()
+        .map(|(name, (p, version))| (name.clone(), (p.version.clone(), version.clone())))
+        .collect()
+}
+
+/// The stable bump level that took the version from `old` to `new`
+fn bump_level(old: &Version, new: &Version) -> Bump {
+    if new.major != old.major {
+        Bump::Major
+    } else if new.minor != old.minor {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    }
+}
+
+/// Whether going from `old` to `new` stays within the largest release allowed
+fn release_allowed(max_bump: ReleaseLevel, old: &Version, new: &Version) -> bool {
+    let level = match bump_level(old, new) {
+        Bump::Major => ReleaseLevel::Major,
+        Bump::Minor => ReleaseLevel::Minor,
+        _ => ReleaseLevel::Patch,
+    };
+
+    match max_bump {
+        ReleaseLevel::Prerelease => new.is_prerelease(),
+        max_bump => level <= max_bump,
+    }
+}
+
+/// Indices of the version items allowed under the branch policy, custom items are always
+/// offered as their version is only known once entered
+fn allowed_items(
+    items: &[(String, Option<Version>)],
+    cur_version: &Version,
+    max_bump: ReleaseLevel,
+) -> Vec<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, version))| {
+            version
+                .as_ref()
+                .is_none_or(|x| release_allowed(max_bump, cur_version, x))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn branch_policy_error(
+    branch: &str,
+    max_bump: ReleaseLevel,
+    name: &str,
+    version: &Version,
+) -> Error {
+    Error::BranchPolicy {
+        branch: branch.to_string(),
+        limit: max_bump.releases(),
+        name: name.to_string(),
+        version: version.to_string(),
+    }
+}
+
+/// Ask which of the offered crates to release
+fn prompt_pkgs(items: &[String], defaults: &[bool]) -> Result<Vec<usize>> {
+    let prompt = "Which crates should be released?";
+    interactive(prompt, "`--force` or `--ignore-changes`")?;
+
+    Ok(MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(defaults)
+        .interact_on(&TERM_ERR)?)
+}
+
+/// Let `pick` choose the crates to release, grouped and with the changed crates picked.
+/// Excluded crates are never offered
+fn select_pkgs(
+    changed: &mut Vec<((GroupName, Option<Version>), Pkg)>,
+    unchanged: &mut Vec<((GroupName, Option<Version>), Pkg)>,
+    pick: impl FnOnce(&[String], &[bool]) -> Result<Vec<usize>>,
+) -> Result {
+    let (excluded, mut pkgs) = std::mem::take(changed)
+        .into_iter()
+        .map(|x| (x, true))
+        .chain(std::mem::take(unchanged).into_iter().map(|x| (x, false)))
+        .partition::<Vec<_>, _>(|(((group_name, _), _), _)| group_name == &GroupName::Excluded);
+
+    pkgs.sort_by_key(|(((group_name, _), p), _)| {
+        (
+            group_name != &GroupName::Default,
+            group_name.to_string(),
+            p.name.clone(),
+        )
+    });
+
+    let items = pkgs
+        .iter()
+        .map(|(((group_name, _), p), _)| match group_name.pretty_fmt() {
+            Some(group) => format!("{} {}", group, p.name),
+            None => p.name.clone(),
+        })
+        .collect::<Vec<_>>();
+    let defaults = pkgs.iter().map(|(_, changed)| *changed).collect::<Vec<_>>();
+
+    let selected = pick(&items, &defaults)?;
+
+    let (selected, rest) = pkgs
+        .into_iter()
+        .enumerate()
+        .partition::<Vec<_>, _>(|(i, _)| selected.contains(i));
+
+    changed.extend(selected.into_iter().map(|(_, (x, _))| x));
+    unchanged.extend(rest.into_iter().map(|(_, (x, _))| x));
+    unchanged.extend(excluded.into_iter().map(|(x, _)| x));
+
+    Ok(())
+}
+
+/// Crates in fixed groups are released together, so move the unchanged crates of every group
+/// with a changed crate along with it
+fn fixed_mates(
+    config: &WorkspaceConfig,
+    changed: &mut Vec<((GroupName, Option<Version>), Pkg)>,
+    unchanged: Vec<((GroupName, Option<Version>), Pkg)>,
+) -> Vec<((GroupName, Option<Version>), Pkg)> {
+    let (mates, rest) = unchanged
+        .into_iter()
+        .partition::<Vec<_>, _>(|((group_name, _), _)| {
+            config.policy(group_name) == GroupPolicy::Fixed
+                && changed.iter().any(|((x, _), _)| x == group_name)
+        });
+
+    changed.extend(mates);
+    rest
+}
+
+/// Graduate prereleases of the tag to their stable version when exiting prerelease mode
+fn graduation(pre: Option<&PreState>, version: &Version) -> Option<BumpSpec> {
+    match pre {
+        Some(pre) if pre.mode == PreMode::Exit && pre.is_tagged(version) => {
+            Some(BumpSpec::Version(stable(version)))
+        }
+        _ => None,
+    }
+}
+
+fn edit_manifest<'a>(
+    manifests: &'a mut Map<Utf8PathBuf, (String, String)>,
+    path: &Utf8Path,
+) -> Result<&'a mut String> {
+    if !manifests.contains_key(path) {
+        let manifest = fs::read_to_string(path)?;
+        manifests.insert(path.to_path_buf(), (manifest.clone(), manifest));
+    }
+
+    Ok(&mut manifests.get_mut(path).expect(INTERNAL_ERR).1)
+}
+
+fn print_diff(path: &Utf8Path, old: &str, new: &str) -> Result {
+    if old == new {
+        return Ok(());
+    }
+
+    let diff = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string();
+
+    for line in diff.lines() {
+        let line = if line.starts_with("+++") || line.starts_with("---") {
+            style(line).bold()
+        } else if line.starts_with('+') {
+            style(line).green()
+        } else if line.starts_with('-') {
+            style(line).red()
+        } else if line.starts_with("@@") {
+            style(line).cyan()
+        } else {
+            style(line)
         };
 
-        Ok(new_version)
+        TERM_OUT.write_line(&line.to_string())?;
     }
+
+    TERM_OUT.write_line("")?;
+
+    Ok(())
 }
 
 fn inc_pre(pre: &[Identifier], preid: &Option<String>) -> Vec<Identifier> {
@@ -857,6 +2244,46 @@ fn version_items(cur_version: &Version, preid: &Option<String>) -> Vec<(String,
     items
 }
 
+/// The prerelease channel of the version, like `beta` in `1.2.0-beta.3`
+fn channel(version: &Version) -> Option<&String> {
+    match version.pre.first() {
+        Some(Identifier::AlphaNumeric(id)) => Some(id),
+        _ => None,
+    }
+}
+
+/// The first prerelease of the version on the channel
+fn on_channel(version: &Version, channel: &str) -> Version {
+    let mut version = stable(version);
+    version.pre = vec![
+        Identifier::AlphaNumeric(channel.to_string()),
+        Identifier::Numeric(0),
+    ];
+    version
+}
+
+fn channel_items(cur_version: &Version, channels: &[String]) -> Vec<(String, Option<Version>)> {
+    let mut items = vec![];
+
+    if cur_version.pre.is_empty() {
+        return items;
+    }
+
+    let v = stable(cur_version);
+    items.push((format!("Graduate ({})", &v), Some(v)));
+
+    let next = channel(cur_version)
+        .and_then(|id| channels.iter().position(|x| x == id))
+        .and_then(|i| channels.get(i + 1));
+
+    if let Some(next) = next {
+        let v = on_channel(cur_version, next);
+        items.push((format!("Promote ({})", &v), Some(v)));
+    }
+
+    items
+}
+
 #[cfg(test)]
 mod test_super {
     use super::*;
@@ -1028,4 +2455,231 @@ mod test_super {
         assert_eq!(v.0, Identifier::Numeric(11));
         assert_eq!(v.1.to_string(), "3.0.0-11.20.a.56.c");
     }
+
+    #[test]
+    fn test_bump_spec() {
+        assert_eq!(
+            parse_bump_crate("foo=Minor").unwrap(),
+            ("foo".to_string(), BumpSpec::Bump(Bump::Minor))
+        );
+        assert_eq!(
+            parse_bump_crate("foo=1.2.3").unwrap(),
+            (
+                "foo".to_string(),
+                BumpSpec::Version(Version::parse("1.2.3").unwrap())
+            )
+        );
+        assert_eq!(
+            parse_bump_group("core==2.0.0-beta.1").unwrap(),
+            (
+                GroupName::Custom("core".to_string()),
+                BumpSpec::Version(Version::parse("2.0.0-beta.1").unwrap())
+            )
+        );
+        assert!(parse_bump_crate("foo=custom").is_err());
+        assert!(parse_bump_crate("foo").is_err());
+    }
+
+    fn channels() -> Vec<String> {
+        DEFAULT_CHANNELS.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_promote() {
+        assert_eq!(
+            parse_promote("alpha->beta").unwrap(),
+            ("alpha".to_string(), "beta".to_string())
+        );
+        assert!(parse_promote("alpha").is_err());
+        assert!(parse_promote("->beta").is_err());
+    }
+
+    #[test]
+    fn test_channel_items() {
+        let items = channel_items(&Version::parse("1.2.0-alpha.3").unwrap(), &channels())
+            .into_iter()
+            .map(|x| x.0)
+            .collect::<Vec<_>>();
+        assert_eq!(items, ["Graduate (1.2.0)", "Promote (1.2.0-beta.0)"]);
+    }
+
+    #[test]
+    fn test_channel_items_last_channel() {
+        let items = channel_items(&Version::parse("1.2.0-rc.3").unwrap(), &channels());
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].1, Some(Version::parse("1.2.0").unwrap()));
+    }
+
+    #[test]
+    fn test_channel_items_on_stable() {
+        assert!(channel_items(&Version::parse("1.2.0").unwrap(), &channels()).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_version() {
+        let change_data = ChangeData {
+            sha: "1a2b3c4".to_string(),
+            count: "3".to_string(),
+            ..Default::default()
+        };
+        let snapshot = |v| snapshot_version(&Version::parse(v).unwrap(), &change_data).to_string();
+
+        assert_eq!(snapshot("0.1.0"), "0.1.1-dev.3+g1a2b3c4");
+        assert_eq!(snapshot("1.0.0-rc.1"), "1.0.0-dev.3+g1a2b3c4");
+
+        let dirty = ChangeData {
+            dirty: true,
+            ..change_data
+        };
+        assert_eq!(
+            snapshot_version(&Version::parse("0.1.0").unwrap(), &dirty).to_string(),
+            "0.1.1-dev.3+g1a2b3c4.dirty"
+        );
+    }
+
+    #[test]
+    fn test_canary_version() {
+        let canary = |v, sha| canary_version(&Version::parse(v).unwrap(), "canary", sha);
+
+        assert_eq!(
+            canary("0.1.0", "1a2b3c4").to_string(),
+            "0.1.1-canary.1a2b3c4"
+        );
+        assert_eq!(
+            canary("1.0.0-rc.1", "1a2b3c4").to_string(),
+            "1.0.0-canary.1a2b3c4"
+        );
+        assert_eq!(
+            canary("0.1.0", "0123456").to_string(),
+            "0.1.1-canary.g0123456"
+        );
+        assert!(Version::parse(&canary("0.1.0", "0123456").to_string()).is_ok());
+    }
+
+    #[test]
+    fn test_release_allowed() {
+        let allowed = |max_bump, old, new| {
+            release_allowed(
+                max_bump,
+                &Version::parse(old).unwrap(),
+                &Version::parse(new).unwrap(),
+            )
+        };
+
+        assert!(allowed(ReleaseLevel::Patch, "1.2.3", "1.2.4"));
+        assert!(allowed(ReleaseLevel::Patch, "1.2.3", "1.2.4-rc.1"));
+        assert!(!allowed(ReleaseLevel::Patch, "1.2.3", "1.3.0"));
+        assert!(!allowed(ReleaseLevel::Patch, "1.2.3", "1.3.0-rc.1"));
+        assert!(allowed(ReleaseLevel::Minor, "1.2.3", "1.3.0"));
+        assert!(!allowed(ReleaseLevel::Minor, "1.2.3", "2.0.0"));
+        assert!(allowed(ReleaseLevel::Prerelease, "1.2.3", "2.0.0-alpha.0"));
+        assert!(!allowed(ReleaseLevel::Prerelease, "1.3.0-rc.1", "1.3.0"));
+    }
+
+    #[test]
+    fn test_lts_branch_policy() {
+        let config: WorkspaceConfig = crate::utils::read_config(&serde_json::json!({
+            "workspaces": {
+                "branch": [
+                    { "name": "release/*", "max_bump": "patch" },
+                    { "name": "next", "max_bump": "prerelease" },
+                ]
+            }
+        }))
+        .unwrap();
+
+        let max_bump = config
+            .branch_policy("release/1.x")
+            .unwrap()
+            .unwrap()
+            .max_bump;
+        assert_eq!(max_bump, ReleaseLevel::Patch);
+        assert!(config.branch_policy("main").unwrap().is_none());
+
+        let cur = Version::parse("1.4.2").unwrap();
+        let mut items = version_items(&cur, &None);
+        items.push(("Custom Version".to_string(), None));
+
+        let offered = allowed_items(&items, &cur, max_bump)
+            .into_iter()
+            .map(|i| items[i].0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            offered,
+            vec![
+                "Patch (1.4.3)",
+                "Prepatch (1.4.3-alpha.0)",
+                "Custom Version"
+            ]
+        );
+
+        let err = branch_policy_error("release/1.x", max_bump, "foo", &"1.5.0".parse().unwrap());
+        assert_eq!(
+            err.to_string(),
+            "the branch release/1.x is limited to patch releases, but foo would be released as 1.5.0"
+        );
+    }
+
+    #[test]
+    fn test_bump_level() {
+        let level =
+            |old, new| bump_level(&Version::parse(old).unwrap(), &Version::parse(new).unwrap());
+
+        assert_eq!(level("1.2.3", "2.0.0"), Bump::Major);
+        assert_eq!(level("1.2.3", "1.3.0"), Bump::Minor);
+        assert_eq!(level("1.2.3", "1.2.4"), Bump::Patch);
+        assert_eq!(level("1.3.0-rc.1", "1.3.0"), Bump::Patch);
+    }
+
+    fn normal_fixture() -> Metadata {
+        cargo_metadata::MetadataCommand::new()
+            .manifest_path("../fixtures/normal/Cargo.toml")
+            .no_deps()
+            .exec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_select_bumps_dependents() {
+        let metadata = normal_fixture();
+        let config: WorkspaceConfig = crate::utils::read_config(&serde_json::json!({
+            "workspaces": { "dependent_bump": "patch" }
+        }))
+        .unwrap();
+        let opt = VersionOpt::parse_from(["version", "patch", "--no-git", "--select", "-y"]);
+
+        let release = opt
+            .plan_release_with(&metadata, &config, true, |items, defaults| {
+                assert!(defaults.iter().all(|x| *x));
+                Ok(vec![items.iter().position(|x| x == "dep2").unwrap()])
+            })
+            .unwrap()
+            .unwrap();
+
+        // dep1 wasn't picked, and top is released as a dependent of dep2
+        let released = release
+            .new_versions
+            .iter()
+            .map(|(name, (_, version))| (name.as_str(), version.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            released,
+            vec![("dep2", "0.1.1".to_string()), ("top", "0.1.1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_select_not_interactive() {
+        crate::utils::set_no_interactive();
+
+        let metadata = normal_fixture();
+        let config = WorkspaceConfig::default();
+        let opt = VersionOpt::parse_from(["version", "patch", "--no-git", "--select", "-y"]);
+
+        let err = opt.plan_release(&metadata, &config, true).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot ask \"Which crates should be released?\" in non-interactive mode, answer it with `--force` or `--ignore-changes`"
+        );
+    }
 }
@@ -0,0 +1,134 @@
+use crate::utils::{Error, Result, INTERNAL_ERR};
+
+use regex::{Captures, Regex};
+use semver::Version;
+use serde::Deserialize;
+
+const VERSION_PATTERN: &str = r"(\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)";
+
+/// A file embedding a version, which is kept in sync when versioning
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct VersionFileSpec {
+    /// Path of the file, relative to the workspace root
+    pub path: String,
+    /// Regex whose `version` group, or else first group, is the version
+    pub regex: Option<String>,
+    /// Literal text where `%v` is the version
+    pub template: Option<String>,
+    /// Crate whose version is embedded, the workspace version when not given
+    #[serde(rename = "crate")]
+    pub krate: Option<String>,
+}
+
+impl VersionFileSpec {
+    fn err(&self, msg: &str) -> Error {
+        Error::BadVersionFile {
+            path: self.path.clone(),
+            msg: msg.to_string(),
+        }
+    }
+
+    fn pattern(&self) -> Result<Regex> {
+        let pattern = match (&self.regex, &self.template) {
+            (Some(regex), None) => regex.clone(),
+            (None, Some(template)) if template.contains("%v") => {
+                regex::escape(template).replace("%v", VERSION_PATTERN)
+            }
+            (None, Some(_)) => return Err(self.err("template must contain '%v'")),
+            _ => return Err(self.err("expected either a regex or a template")),
+        };
+
+        Regex::new(&pattern).map_err(|err| self.err(&err.to_string()))
+    }
+
+    /// Replace every version matched in the contents with the given version
+    pub fn sync(&self, contents: &str, version: &Version) -> Result<String> {
+        let pattern = self.pattern()?;
+        let mut matched = false;
+
+        let synced = pattern.replace_all(contents, |caps: &Captures| {
+            matched = true;
+
+            let whole = caps.get(0).expect(INTERNAL_ERR);
+            let found = caps
+                .name("version")
+                .or_else(|| caps.get(1))
+                .unwrap_or(whole);
+
+            format!(
+                "{}{}{}",
+                &contents[whole.start()..found.start()],
+                version,
+                &contents[found.end()..whole.end()]
+            )
+        });
+
+        if !matched {
+            return Err(self.err("no version was found"));
+        }
+
+        Ok(synced.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spec(regex: Option<&str>, template: Option<&str>) -> VersionFileSpec {
+        VersionFileSpec {
+            path: "README.md".to_string(),
+            regex: regex.map(|x| x.to_string()),
+            template: template.map(|x| x.to_string()),
+            krate: None,
+        }
+    }
+
+    fn sync(spec: &VersionFileSpec, contents: &str) -> Result<String> {
+        spec.sync(contents, &Version::parse("1.3.0").unwrap())
+    }
+
+    #[test]
+    fn test_template() {
+        let spec = spec(None, Some(r#"foo = "%v""#));
+        assert_eq!(
+            sync(
+                &spec,
+                "[dependencies]\nfoo = \"1.2.0-rc.1\"\nbar = \"1.2.0\"\n"
+            )
+            .unwrap(),
+            "[dependencies]\nfoo = \"1.3.0\"\nbar = \"1.2.0\"\n"
+        );
+    }
+
+    #[test]
+    fn test_regex_group() {
+        let spec = spec(Some(r"FROM foo:(\S+) AS (\w+)"), None);
+        assert_eq!(
+            sync(&spec, "FROM foo:1.2.0 AS build\n").unwrap(),
+            "FROM foo:1.3.0 AS build\n"
+        );
+    }
+
+    #[test]
+    fn test_regex_named_group() {
+        let spec = spec(Some(r"(v)(?P<version>[\d.]+)"), None);
+        assert_eq!(
+            sync(&spec, "v1.2.0 and v1.1.0").unwrap(),
+            "v1.3.0 and v1.3.0"
+        );
+    }
+
+    #[test]
+    fn test_unmatched() {
+        assert!(sync(&spec(None, Some("foo@%v")), "bar@1.2.0").is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(sync(&spec(None, Some("foo")), "foo").is_err());
+        assert!(sync(&spec(None, None), "foo").is_err());
+        assert!(sync(&spec(Some("("), None), "foo").is_err());
+    }
+}
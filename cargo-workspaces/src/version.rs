@@ -1,4 +1,5 @@
-use crate::utils::{info, read_config, Result, VersionOpt};
+use crate::utils::{info, read_config, Error, Plan, Result, VersionOpt};
+use camino::Utf8PathBuf;
 use cargo_metadata::Metadata;
 use clap::Parser;
 
@@ -7,18 +8,135 @@ use clap::Parser;
 pub struct Version {
     #[clap(flatten)]
     version: VersionOpt,
+
+    /// Print the manifest changes, commit and tags without writing anything
+    #[clap(long, conflicts_with_all = &["plan-out", "plan-in"])]
+    dry_run: bool,
+
+    /// Write the computed release plan to this file instead of applying it
+    #[clap(long, value_name = "path", conflicts_with = "plan-in")]
+    plan_out: Option<Utf8PathBuf>,
+
+    /// Apply a release plan written by `--plan-out`, without prompts
+    #[clap(
+        long,
+        value_name = "path",
+        conflicts_with_all = &[
+            "bump", "custom", "pre-id", "bump-crate", "bump-group", "graduate",
+            "promote", "force", "ignore-changes", "include-merged-tags", "groups",
+            "select"
+        ]
+    )]
+    plan_in: Option<Utf8PathBuf>,
+
+    /// Reset the last release commit and delete its tags
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "dry-run", "plan-out", "plan-in", "bump", "custom", "bump-crate",
+            "bump-group", "graduate", "promote", "force"
+        ]
+    )]
+    undo: bool,
+
+    /// Also delete the tags of the undone release from the remote
+    #[clap(long, requires = "undo")]
+    remote: bool,
+
+    /// Rewrite the changed crates to `dev` prereleases of the current commit, without committing
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "dry-run", "plan-out", "plan-in", "undo", "bump", "custom", "bump-crate",
+            "bump-group", "graduate", "promote", "select", "no-git"
+        ]
+    )]
+    snapshot: bool,
+
+    /// Put back the versions rewritten by `--snapshot`
+    #[clap(
+        long,
+        conflicts_with_all = &["dry-run", "plan-out", "plan-in", "undo", "snapshot"]
+    )]
+    restore: bool,
 }
 
 impl Version {
-    pub fn run(self, metadata: Metadata) -> Result {
+    pub fn run(mut self, metadata: Metadata) -> Result {
         let config = read_config(&metadata.workspace_metadata)?;
+        self.version.configure(&config)?;
+
+        if self.undo || self.snapshot || self.restore {
+            if self.undo {
+                self.version.undo(&metadata, &config, self.remote)?;
+            } else if self.snapshot {
+                self.version.snapshot(&metadata, &config)?;
+            } else {
+                self.version.restore(&metadata)?;
+            }
+
+            info!("success", "ok");
+
+            return Ok(());
+        }
+
+        if self.dry_run || self.plan_out.is_some() {
+            if let Some(release) = self.version.plan_release(&metadata, &config, true)? {
+                if let Some(path) = &self.plan_out {
+                    let tags = self
+                        .version
+                        .git
+                        .tag_names(&release.new_version, &release.new_versions);
+
+                    Plan::new(
+                        &metadata,
+                        &config,
+                        release.since,
+                        &release.new_version,
+                        &release.new_versions,
+                        tags,
+                    )?
+                    .write(path)?;
+
+                    info!("plan", format!("written to {}", path));
+                } else {
+                    self.version
+                        .write_release(&metadata, &config, &release, true)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        let branch = self.version.git.validate(&metadata, &config)?;
+
+        let versioned = if let Some(path) = &self.plan_in {
+            let plan = Plan::read(path)?;
+            plan.verify(&metadata, &config)?;
+
+            let planned_tags = plan.tags.clone();
+            let release = plan.release(&metadata, &config)?;
+
+            let tags = self
+                .version
+                .git
+                .tag_names(&release.new_version, &release.new_versions);
+            if tags != planned_tags {
+                return Err(Error::PlanTagsMismatch {
+                    planned: planned_tags,
+                    actual: tags,
+                });
+            }
+
+            self.version
+                .write_release(&metadata, &config, &release, false)?;
 
-        let branch = self
-            .version
-            .git
-            .validate(&metadata.workspace_root, &config)?;
+            Some((release.new_version, release.new_versions))
+        } else {
+            self.version.do_versioning(&metadata, &config)?
+        };
 
-        if let Some((new_version, new_versions)) = self.version.do_versioning(&metadata, &config)? {
+        if let Some((new_version, new_versions)) = versioned {
             let mut tags = vec![];
             for (_, (pkg, ver)) in &new_versions {
                 if let Some(tag) = self.version.git.individual_tag(
@@ -26,7 +144,6 @@ impl Version {
                     &pkg.name,
                     pkg.private,
                     &ver.to_string(),
-                    &config,
                 )? {
                     tags.push(tag)
                 }
@@ -1,6 +1,10 @@
 #![allow(dead_code)]
 use assert_cmd::Command;
-use std::str::from_utf8;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::from_utf8,
+};
 
 pub fn run(dir: &str, args: &[&str]) -> (String, String) {
     let output = Command::cargo_bin("cargo-ws")
@@ -29,3 +33,29 @@ pub fn run_err(dir: &str, args: &[&str]) -> String {
     assert!(out.is_empty());
     err
 }
+
+/// Copy a fixture to a fresh temporary directory, for tests that change the workspace
+pub fn copy_fixture(fixture: &str, name: &str) -> String {
+    let dir = env::temp_dir().join(format!("cargo-workspaces-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+
+    copy_dir(&Path::new("../fixtures").join(fixture), &dir);
+    dir.to_str().unwrap().to_string()
+}
+
+fn copy_dir(from: &Path, to: &PathBuf) {
+    fs::create_dir_all(to).unwrap();
+
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let path = entry.path();
+
+        if path.is_dir() {
+            if entry.file_name() != "target" {
+                copy_dir(&path, &to.join(entry.file_name()));
+            }
+        } else {
+            fs::copy(&path, to.join(entry.file_name())).unwrap();
+        }
+    }
+}
@@ -0,0 +1,138 @@
+mod utils;
+use insta::assert_snapshot;
+use std::{fs, path::Path};
+
+fn changesets(dir: &str) -> Vec<String> {
+    let dir = Path::new(dir).join(".changeset");
+
+    if !dir.is_dir() {
+        return vec![];
+    }
+
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|x| x.unwrap().file_name().to_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_changeset_consumed() {
+    let dir = utils::copy_fixture("normal", "version-changeset");
+
+    let err = utils::run_err(
+        &dir,
+        &[
+            "ws",
+            "changeset",
+            "add",
+            "--bump",
+            "dep1=patch",
+            "-m",
+            "Fix the thing",
+        ],
+    );
+    assert!(err.contains("created .changeset/fix-the-thing-"));
+    assert_eq!(changesets(&dir).len(), 1);
+
+    let err = utils::run_err(&dir, &["ws", "version", "--no-git", "-y"]);
+    assert_snapshot!(err);
+
+    assert!(changesets(&dir).is_empty());
+    assert_snapshot!(fs::read_to_string(Path::new(&dir).join("dep1/Cargo.toml")).unwrap());
+}
+
+#[test]
+fn test_dry_run() {
+    let dir = utils::copy_fixture("normal", "version-dry-run");
+    let manifest = fs::read_to_string(Path::new(&dir).join("top/Cargo.toml")).unwrap();
+
+    utils::run_err(
+        &dir,
+        &[
+            "ws",
+            "changeset",
+            "add",
+            "--bump",
+            "dep2=patch",
+            "-m",
+            "Fix the thing",
+        ],
+    );
+
+    let (out, err) = utils::run(&dir, &["ws", "version", "--no-git", "-y", "--dry-run"]);
+    assert_snapshot!(err);
+    assert_snapshot!(out);
+
+    assert_eq!(changesets(&dir).len(), 1);
+    assert!(!Path::new(&dir).join("Cargo.lock").exists());
+    assert_eq!(
+        fs::read_to_string(Path::new(&dir).join("top/Cargo.toml")).unwrap(),
+        manifest
+    );
+}
+
+fn git(dir: &str, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+}
+
+#[test]
+fn test_unpushed_commits_without_push() {
+    let dir = utils::copy_fixture("normal", "version-unpushed");
+    let remote = format!("{}-remote", dir);
+    let _ = fs::remove_dir_all(&remote);
+
+    git(&dir, &["init", "-q", "-b", "master"]);
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "init"]);
+    git(&dir, &["init", "-q", "--bare", &remote]);
+    git(&dir, &["remote", "add", "origin", &remote]);
+    git(&dir, &["push", "-q", "origin", "master"]);
+
+    fs::write(Path::new(&dir).join("README.md"), "unpushed").unwrap();
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "unpushed"]);
+
+    let err = utils::run_err(&dir, &["ws", "version", "patch", "-y", "--no-git-push"]);
+    assert!(err.contains("local branch master has 1 commits not pushed to origin/master"));
+}
+
+#[test]
+fn test_not_interactive() {
+    let dir = utils::copy_fixture("normal", "version-not-interactive");
+
+    // The output is captured and stdin isn't a terminal, so prompting fails instead of waiting
+    let version = |args: &[&str]| {
+        let output = assert_cmd::Command::cargo_bin("cargo-ws")
+            .unwrap()
+            .current_dir(&dir)
+            .args(args)
+            .timeout(std::time::Duration::from_secs(30))
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let err = version(&["ws", "version", "--no-git"]);
+    assert!(err.contains(
+        "cannot ask \"Select a new version for the workspace\" in non-interactive mode, answer it with a bump"
+    ));
+
+    let err = version(&["ws", "version", "--no-git", "patch"]);
+    assert!(err.contains(
+        "cannot ask \"Are you sure you want to create these versions?\" in non-interactive mode, answer it with `--yes`"
+    ));
+
+    assert!(!Path::new(&dir).join("Cargo.lock").exists());
+}